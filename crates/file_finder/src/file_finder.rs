@@ -891,6 +891,8 @@ fn parse_file_search_query(raw_query: &str) -> FileSearchQuery {
                 path: PathBuf::from(&path_query),
                 row: Some(start_line),
                 column: None,
+                offset: None,
+                context: None,
             },
             line_range: end_line.map(|end| start_line..=end),
         };
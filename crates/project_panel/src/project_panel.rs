@@ -7430,7 +7430,13 @@ fn cmp_worktree_entries(
 ) -> cmp::Ordering {
     let a = (&*a.path, a.is_file());
     let b = (&*b.path, b.is_file());
-    util::paths::compare_rel_paths_by(a, b, (*mode).into(), (*order).into())
+    util::paths::compare_rel_paths_by(
+        a,
+        b,
+        (*mode).into(),
+        (*order).into(),
+        util::paths::SortDirection::Ascending,
+    )
 }
 
 pub fn sort_worktree_entries(
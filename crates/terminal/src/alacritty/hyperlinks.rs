@@ -1800,7 +1800,9 @@ mod tests {
                     &PathWithPosition {
                         path: PathBuf::from(self.expected_hyperlink.iri_or_path.clone()),
                         row: self.expected_hyperlink.row,
-                        column: self.expected_hyperlink.column
+                        column: self.expected_hyperlink.column,
+                        offset: None,
+                        context: None
                     },
                     &self.expected_hyperlink.hyperlink_match
                 ),
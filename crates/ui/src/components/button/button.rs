@@ -178,6 +178,13 @@ impl Button {
         self
     }
 
+    /// Caps the button's width. Combine with [`Button::truncate`] so that a long label is
+    /// ellipsized instead of clipped.
+    pub fn max_width(mut self, max_width: impl Into<DefiniteLength>) -> Self {
+        self.base = self.base.max_width(max_width);
+        self
+    }
+
     /// Truncates overflowing labels with an ellipsis (`…`) if needed.
     ///
     /// Buttons with static labels should _never_ be truncated, ensure
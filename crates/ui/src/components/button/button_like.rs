@@ -487,6 +487,7 @@ pub struct ButtonLike {
     pub(super) selected: bool,
     pub(super) selected_style: Option<ButtonStyle>,
     pub(super) width: Option<DefiniteLength>,
+    max_width: Option<DefiniteLength>,
     pub(super) height: Option<DefiniteLength>,
     pub(super) layer: Option<ElevationIndex>,
     tab_index: Option<isize>,
@@ -511,6 +512,7 @@ impl ButtonLike {
             selected: false,
             selected_style: None,
             width: None,
+            max_width: None,
             height: None,
             size: ButtonSize::Default,
             rounding: Some(ButtonLikeRounding::ALL),
@@ -548,6 +550,12 @@ impl ButtonLike {
         self
     }
 
+    /// Caps the button's width, clipping any content that doesn't fit.
+    pub fn max_width(mut self, max_width: impl Into<DefiniteLength>) -> Self {
+        self.max_width = Some(max_width.into());
+        self
+    }
+
     pub(crate) fn rounding(mut self, rounding: impl Into<Option<ButtonLikeRounding>>) -> Self {
         self.rounding = rounding.into();
         self
@@ -690,6 +698,9 @@ impl RenderOnce for ButtonLike {
             .when_some(self.width, |this, width| {
                 this.w(width).justify_center().text_center()
             })
+            .when_some(self.max_width, |this, max_width| {
+                this.max_w(max_width).overflow_hidden()
+            })
             .when(is_outlined, |this| this.border_1())
             .when_some(self.rounding, |this, rounding| {
                 this.when(rounding.top_left, |this| this.rounded_tl_sm())
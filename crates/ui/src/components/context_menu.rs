@@ -1,11 +1,13 @@
 use crate::{
     ButtonCommon, ButtonStyle, IconButtonShape, KeyBinding, List, ListItem, ListSeparator,
-    ListSubHeader, Tooltip, prelude::*, utils::WithRemSize,
+    ListSubHeader, Tooltip,
+    prelude::*,
+    utils::{WithRemSize, score_match},
 };
 use gpui::{
     Action, Anchor, AnyElement, App, Bounds, DismissEvent, Entity, EventEmitter, FocusHandle,
-    Focusable, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point, Size,
-    Subscription, TaskExt, anchored, canvas, prelude::*, px,
+    Focusable, KeyDownEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
+    Point, Size, Subscription, TaskExt, anchored, canvas, prelude::*, px,
 };
 use menu::{SelectChild, SelectFirst, SelectLast, SelectNext, SelectParent, SelectPrevious};
 use std::{
@@ -233,6 +235,8 @@ pub struct ContextMenu {
     submenu_trigger_bounds: Rc<Cell<Option<Bounds<Pixels>>>>,
     submenu_trigger_mouse_down: bool,
     ignore_blur_until: Option<Instant>,
+    /// The type-to-filter query, present only when the menu is searchable.
+    search_query: Option<String>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -320,6 +324,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                search_query: None,
             },
             window,
             cx,
@@ -397,6 +402,7 @@ impl ContextMenu {
                     submenu_trigger_bounds: Rc::new(Cell::new(None)),
                     submenu_trigger_mouse_down: false,
                     ignore_blur_until: None,
+                    search_query: None,
                 },
                 window,
                 cx,
@@ -466,6 +472,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                search_query: None,
             },
             window,
             cx,
@@ -889,6 +896,112 @@ impl ContextMenu {
         self
     }
 
+    /// Filters the menu's entries by what the user types while it is focused, showing the
+    /// query above the entries.
+    pub fn searchable(mut self, searchable: bool) -> Self {
+        self.search_query = searchable.then(String::new);
+        self
+    }
+
+    /// Like [`ContextMenu::searchable`], for menus that are already built. Clears any query
+    /// typed so far.
+    pub fn set_searchable(&mut self, searchable: bool, cx: &mut Context<Self>) {
+        self.search_query = searchable.then(String::new);
+        self.selected_index = None;
+        cx.notify();
+    }
+
+    fn set_search_query(&mut self, query: String, window: &mut Window, cx: &mut Context<Self>) {
+        if self.search_query.is_none() {
+            return;
+        }
+        self.search_query = Some(query);
+        self.selected_index = None;
+        self.select_first(&SelectFirst, window, cx);
+    }
+
+    fn handle_search_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(mut query) = self.search_query.clone() else {
+            return;
+        };
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control || keystroke.modifiers.platform {
+            return;
+        }
+        if keystroke.key == "backspace" {
+            if query.pop().is_none() {
+                return;
+            }
+        } else if let Some(text) = keystroke
+            .key_char
+            .as_ref()
+            .filter(|text| !text.chars().any(char::is_control))
+        {
+            query.push_str(text);
+        } else {
+            return;
+        }
+        cx.stop_propagation();
+        self.set_search_query(query, window, cx);
+    }
+
+    fn active_search_query(&self) -> Option<&str> {
+        self.search_query
+            .as_deref()
+            .filter(|query| !query.is_empty())
+    }
+
+    /// Scores how well the item matches the search query, or returns `None` if it's hidden by
+    /// it. Every item matches with a score of 0 while there's no query.
+    fn search_score(&self, item: &ContextMenuItem) -> Option<i32> {
+        let Some(query) = self.active_search_query() else {
+            return Some(0);
+        };
+        match item {
+            ContextMenuItem::Entry(ContextMenuEntry { label, .. })
+            | ContextMenuItem::Submenu { label, .. } => score_match(query, label),
+            ContextMenuItem::Separator
+            | ContextMenuItem::Header(_)
+            | ContextMenuItem::HeaderWithLink(..)
+            | ContextMenuItem::Label(_)
+            | ContextMenuItem::CustomEntry { .. } => None,
+        }
+    }
+
+    /// Indices of the items to show, in display order. While searching, only matching items
+    /// are shown, best matches first.
+    fn visible_item_indices(&self) -> Vec<usize> {
+        let mut scored_indices = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, item)| Some((self.search_score(item)?, ix)))
+            .collect::<Vec<_>>();
+        if self.active_search_query().is_some() {
+            scored_indices.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        }
+        scored_indices.into_iter().map(|(_, ix)| ix).collect()
+    }
+
+    fn visible_items(&self) -> impl Iterator<Item = (usize, &ContextMenuItem)> {
+        self.visible_item_indices()
+            .into_iter()
+            .map(|ix| (ix, &self.items[ix]))
+    }
+
+    /// Selectable items in display order.
+    fn selectable_item_indices(&self) -> Vec<usize> {
+        self.visible_item_indices()
+            .into_iter()
+            .filter(|&ix| self.items[ix].is_selectable())
+            .collect()
+    }
+
     pub fn key_context(mut self, context: impl Into<SharedString>) -> Self {
         self.key_context = context.into();
         self
@@ -898,6 +1011,20 @@ impl ContextMenu {
         self.selected_index
     }
 
+    /// Returns the label and icon of the first toggled-on entry, which is how single-choice
+    /// menus mark their current selection.
+    pub fn checked_entry(&self) -> Option<(SharedString, Option<IconName>)> {
+        self.items.iter().find_map(|item| match item {
+            ContextMenuItem::Entry(ContextMenuEntry {
+                toggle: Some((_, true)),
+                label,
+                icon,
+                ..
+            }) => Some((label.clone(), *icon)),
+            _ => None,
+        })
+    }
+
     pub fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
         let Some(ix) = self.selected_index else {
             return;
@@ -1050,19 +1177,15 @@ impl ContextMenu {
     }
 
     pub fn select_first(&mut self, _: &SelectFirst, window: &mut Window, cx: &mut Context<Self>) {
-        if let Some(ix) = self.items.iter().position(|item| item.is_selectable()) {
+        if let Some(&ix) = self.selectable_item_indices().first() {
             self.select_index(ix, window, cx);
         }
         cx.notify();
     }
 
     pub fn select_last(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Option<usize> {
-        for (ix, item) in self.items.iter().enumerate().rev() {
-            if item.is_selectable() {
-                return self.select_index(ix, window, cx);
-            }
-        }
-        None
+        let ix = *self.selectable_item_indices().last()?;
+        self.select_index(ix, window, cx)
     }
 
     fn handle_select_last(&mut self, _: &SelectLast, window: &mut Window, cx: &mut Context<Self>) {
@@ -1072,19 +1195,16 @@ impl ContextMenu {
     }
 
     pub fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
-        if let Some(ix) = self.selected_index {
-            let next_index = ix + 1;
-            if self.items.len() <= next_index {
-                self.select_first(&SelectFirst, window, cx);
+        if let Some(selected_ix) = self.selected_index {
+            let selectable = self.selectable_item_indices();
+            if let Some(&next_ix) = selectable
+                .iter()
+                .skip_while(|&&ix| ix != selected_ix)
+                .nth(1)
+            {
+                self.select_index(next_ix, window, cx);
+                cx.notify();
                 return;
-            } else {
-                for (ix, item) in self.items.iter().enumerate().skip(next_index) {
-                    if item.is_selectable() {
-                        self.select_index(ix, window, cx);
-                        cx.notify();
-                        return;
-                    }
-                }
             }
         }
         self.select_first(&SelectFirst, window, cx);
@@ -1096,13 +1216,17 @@ impl ContextMenu {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some(ix) = self.selected_index {
-            for (ix, item) in self.items.iter().enumerate().take(ix).rev() {
-                if item.is_selectable() {
-                    self.select_index(ix, window, cx);
-                    cx.notify();
-                    return;
-                }
+        if let Some(selected_ix) = self.selected_index {
+            let selectable = self.selectable_item_indices();
+            if let Some(&previous_ix) = selectable
+                .iter()
+                .rev()
+                .skip_while(|&&ix| ix != selected_ix)
+                .nth(1)
+            {
+                self.select_index(previous_ix, window, cx);
+                cx.notify();
+                return;
             }
         }
         self.handle_select_last(&SelectLast, window, cx);
@@ -1262,6 +1386,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                search_query: None,
             };
 
             menu = (builder)(menu, window, cx);
@@ -2176,6 +2301,9 @@ impl Render for ContextMenu {
                         .on_action(cx.listener(ContextMenu::confirm))
                         .on_action(cx.listener(ContextMenu::secondary_confirm))
                         .on_action(cx.listener(ContextMenu::cancel))
+                        .when(self.search_query.is_some(), |this| {
+                            this.on_key_down(cx.listener(ContextMenu::handle_search_key_down))
+                        })
                         .on_hover(cx.listener(|this, hovered: &bool, _, cx| {
                             if *hovered {
                                 this.hover_target = HoverTarget::MainMenu;
@@ -2229,11 +2357,38 @@ impl Render for ContextMenu {
                             }
                             el
                         })
+                        .when_some(self.search_query.as_ref(), |this, query| {
+                            let has_matches = !self.selectable_item_indices().is_empty();
+                            this.child(
+                                h_flex()
+                                    .px_2()
+                                    .py_1()
+                                    .gap_1p5()
+                                    .border_b_1()
+                                    .border_color(cx.theme().colors().border_variant)
+                                    .child(
+                                        Icon::new(IconName::MagnifyingGlass)
+                                            .size(IconSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                    .child(if query.is_empty() {
+                                        Label::new("Search…").color(Color::Placeholder)
+                                    } else {
+                                        Label::new(query.clone())
+                                    }),
+                            )
+                            .when(!has_matches, |this| {
+                                this.child(
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .child(Label::new("No matches").color(Color::Muted)),
+                                )
+                            })
+                        })
                         .child(
                             List::new().children(
-                                self.items
-                                    .iter()
-                                    .enumerate()
+                                self.visible_items()
                                     .map(|(ix, item)| self.render_menu_item(ix, item, window, cx)),
                             ),
                         ),
@@ -2310,6 +2465,7 @@ impl Render for ContextMenu {
 #[cfg(test)]
 mod tests {
     use gpui::TestAppContext;
+    use itertools::Itertools;
 
     use super::*;
 
@@ -2400,4 +2556,192 @@ mod tests {
             );
         });
     }
+
+    #[gpui::test]
+    fn checked_entry_follows_confirmed_selection(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let selected = Rc::new(std::cell::Cell::new(None));
+        let context_menu = cx.update(|window, cx| {
+            let selected = selected.clone();
+            ContextMenu::build_persistent(window, cx, move |mut menu, _, _| {
+                for (ix, theme) in ["One Dark", "One Light"].into_iter().enumerate() {
+                    let selected = selected.clone();
+                    menu = menu.item(
+                        ContextMenuEntry::new(theme)
+                            .toggleable(IconPosition::End, selected.get() == Some(ix))
+                            .when(ix == 1, |entry| entry.icon(IconName::Star))
+                            .handler(move |_, _| selected.set(Some(ix))),
+                    );
+                }
+                menu
+            })
+        });
+
+        context_menu.update_in(cx, |context_menu, window, cx| {
+            assert_eq!(context_menu.checked_entry(), None);
+
+            context_menu.select_last(window, cx);
+            context_menu.confirm(&menu::Confirm, window, cx);
+            assert_eq!(
+                context_menu.checked_entry(),
+                Some(("One Light".into(), Some(IconName::Star)))
+            );
+
+            context_menu.select_first(&SelectFirst, window, cx);
+            context_menu.confirm(&menu::Confirm, window, cx);
+            assert_eq!(
+                context_menu.checked_entry(),
+                Some(("One Dark".into(), None))
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn search_scores_only_entries_and_submenus(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let context_menu = cx.update(|window, cx| {
+            ContextMenu::build(window, cx, |menu, _, _| {
+                menu.searchable(true)
+                    .header("Light Themes")
+                    .entry("One Light", None, |_, _| {})
+                    .label("Lighting")
+                    .separator()
+                    .submenu("Light Variants", |menu, _, _| menu)
+                    .entry("Ayu Dark", None, |_, _| {})
+                    .entry("Gruvbox Light", None, |_, _| {})
+            })
+        });
+
+        context_menu.update_in(cx, |context_menu, window, cx| {
+            assert!(
+                context_menu
+                    .items
+                    .iter()
+                    .all(|item| context_menu.search_score(item) == Some(0)),
+                "Every item matches while there's no query"
+            );
+            assert_eq!(
+                context_menu.visible_item_indices(),
+                (0..7).collect::<Vec<_>>()
+            );
+
+            context_menu.set_search_query("light".into(), window, cx);
+            let scores = context_menu
+                .items
+                .iter()
+                .map(|item| context_menu.search_score(item))
+                .collect::<Vec<_>>();
+            for ix in [0, 2, 3, 5] {
+                assert_eq!(
+                    None, scores[ix],
+                    "Item {ix} is hidden: only entries and submenus are searched"
+                );
+            }
+            let visible = context_menu.visible_item_indices();
+            assert_eq!(
+                visible.iter().copied().sorted().collect::<Vec<_>>(),
+                vec![1, 4, 6]
+            );
+            assert!(
+                visible
+                    .iter()
+                    .tuple_windows()
+                    .all(|(&a, &b)| scores[a] >= scores[b]),
+                "Best matches come first"
+            );
+
+            context_menu.set_search_query("light variants".into(), window, cx);
+            assert_eq!(context_menu.visible_item_indices(), vec![4]);
+        });
+    }
+
+    #[gpui::test]
+    fn search_query_filters_navigation(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let context_menu = cx.update(|window, cx| {
+            ContextMenu::build(window, cx, |menu, _, _| {
+                menu.searchable(true)
+                    .header("Themes")
+                    .entry("Ayu Dark", None, |_, _| {})
+                    .entry("One Light", None, |_, _| {})
+                    .separator()
+                    .entry("One Dark", None, |_, _| {})
+                    .entry("Gruvbox Light", None, |_, _| {})
+            })
+        });
+
+        context_menu.update_in(cx, |context_menu, window, cx| {
+            context_menu.set_search_query("light".into(), window, cx);
+            assert_eq!(
+                Some(2),
+                context_menu.selected_index,
+                "Should select the first matching entry"
+            );
+
+            context_menu.select_next(&SelectNext, window, cx);
+            assert_eq!(
+                Some(5),
+                context_menu.selected_index,
+                "Should skip entries that don't match the query"
+            );
+
+            context_menu.select_next(&SelectNext, window, cx);
+            assert_eq!(Some(2), context_menu.selected_index);
+
+            context_menu.set_search_query("solarized".into(), window, cx);
+            assert_eq!(
+                None, context_menu.selected_index,
+                "Nothing is selected when no entry matches"
+            );
+
+            context_menu.set_search_query(String::new(), window, cx);
+            assert_eq!(
+                Some(1),
+                context_menu.selected_index,
+                "Clearing the query shows every entry again"
+            );
+
+            context_menu.set_searchable(false, cx);
+            context_menu.set_search_query("light".into(), window, cx);
+            assert_eq!(
+                None, context_menu.search_query,
+                "Queries are ignored when the menu isn't searchable"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn search_query_ranks_best_matches_first(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let context_menu = cx.update(|window, cx| {
+            ContextMenu::build(window, cx, |menu, _, _| {
+                menu.searchable(true)
+                    .entry("fizzbuzz", None, |_, _| {})
+                    .entry("Dark", None, |_, _| {})
+                    .entry("foobar", None, |_, _| {})
+            })
+        });
+
+        context_menu.update_in(cx, |context_menu, window, cx| {
+            context_menu.set_search_query("fb".into(), window, cx);
+            assert_eq!(context_menu.visible_item_indices(), [2, 0]);
+            assert_eq!(
+                Some(2),
+                context_menu.selected_index,
+                "Should select the best match"
+            );
+
+            context_menu.select_next(&SelectNext, window, cx);
+            assert_eq!(Some(0), context_menu.selected_index);
+
+            context_menu.select_next(&SelectNext, window, cx);
+            assert_eq!(Some(2), context_menu.selected_index);
+
+            context_menu.select_previous(&SelectPrevious, window, cx);
+            assert_eq!(Some(0), context_menu.selected_index);
+
+            context_menu.set_search_query(String::new(), window, cx);
+            assert_eq!(context_menu.visible_item_indices(), [0, 1, 2]);
+        });
+    }
 }
@@ -1,6 +1,11 @@
-use gpui::{Anchor, AnyView, Entity, Pixels, Point};
+use std::rc::Rc;
 
-use crate::{ButtonLike, ContextMenu, PopoverMenu, prelude::*};
+use gpui::{Anchor, AnyView, Entity, Pixels, Point, Task};
+
+use crate::{
+    ButtonLike, CommonAnimationExt, ContextMenu, ContextMenuEntry, PopoverMenu, PopoverTrigger,
+    Tooltip, prelude::*,
+};
 
 use super::PopoverMenuHandle;
 
@@ -13,27 +18,143 @@ pub enum DropdownStyle {
     Ghost,
 }
 
+/// The overall size of a [`DropdownMenu`] trigger, scaling its padding, label and icons together.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropdownSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl DropdownSize {
+    fn button_size(self) -> ButtonSize {
+        match self {
+            DropdownSize::Small => ButtonSize::Compact,
+            DropdownSize::Medium => ButtonSize::Default,
+            DropdownSize::Large => ButtonSize::Medium,
+        }
+    }
+
+    fn label_size(self) -> LabelSize {
+        match self {
+            DropdownSize::Small => LabelSize::Small,
+            DropdownSize::Medium => LabelSize::Default,
+            DropdownSize::Large => LabelSize::Large,
+        }
+    }
+
+    fn icon_size(self) -> IconSize {
+        match self {
+            DropdownSize::Small => IconSize::Indicator,
+            DropdownSize::Medium => IconSize::XSmall,
+            DropdownSize::Large => IconSize::Small,
+        }
+    }
+}
+
+/// Whether a [`DropdownMenu`]'s options are available yet.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum DropdownState {
+    #[default]
+    Ready,
+    /// Shows a spinner in place of the chevron and keeps the menu from opening.
+    Loading,
+    /// Colors the trigger as an error and shows the message as its tooltip.
+    Error(SharedString),
+}
+
+/// An option in a [`DropdownMenu`] built with [`DropdownMenu::from_sections`].
+#[derive(Clone)]
+pub struct DropdownItem {
+    label: SharedString,
+    icon: Option<IconName>,
+    on_select: Rc<dyn Fn(&mut Window, &mut App)>,
+}
+
+impl DropdownItem {
+    pub fn new(
+        label: impl Into<SharedString>,
+        on_select: impl Fn(&mut Window, &mut App) + 'static,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            icon: None,
+            on_select: Rc::new(on_select),
+        }
+    }
+
+    /// Shows an icon before the option's label.
+    pub fn icon(mut self, icon: IconName) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+}
+
 enum LabelKind {
     Text(SharedString),
     Element(AnyElement),
 }
 
+/// Where a [`DropdownMenu`] gets its menu from when it's opened.
+enum MenuSource {
+    Build(Rc<dyn Fn(&mut Window, &mut App) -> Entity<ContextMenu>>),
+    /// Loaded on first open by [`DropdownMenu::new_async`], then reused.
+    Load(MenuLoader),
+}
+
+type MenuLoader = Rc<dyn Fn(&mut Window, &mut App) -> Task<anyhow::Result<Entity<ContextMenu>>>>;
+
+/// How far an async dropdown has got with loading its menu, kept across renders.
+#[derive(Default)]
+enum AsyncMenu {
+    #[default]
+    NotLoaded,
+    Loading {
+        _task: Task<()>,
+    },
+    Loaded(Entity<ContextMenu>),
+    Failed(SharedString),
+}
+
+/// Attaches a caller-supplied trigger to the popover, given whether it should be disabled.
+type CustomTrigger =
+    Box<dyn FnOnce(PopoverMenu<ContextMenu>, bool) -> PopoverMenu<ContextMenu> + 'static>;
+
 #[derive(IntoElement, RegisterComponent)]
 pub struct DropdownMenu {
     id: ElementId,
     label: LabelKind,
+    custom_trigger: Option<CustomTrigger>,
+    icon: Option<IconName>,
+    placeholder: Option<SharedString>,
+    selected_label: Option<SharedString>,
+    size: DropdownSize,
     trigger_size: ButtonSize,
     trigger_tooltip: Option<Box<dyn Fn(&mut Window, &mut App) -> AnyView + 'static>>,
     trigger_icon: Option<IconName>,
     style: DropdownStyle,
-    menu: Entity<ContextMenu>,
+    menu: MenuSource,
+    bound_menu: Option<Entity<ContextMenu>>,
+    label_from_selection: bool,
     full_width: bool,
+    max_width: Option<Pixels>,
     disabled: bool,
+    disabled_message: Option<SharedString>,
     handle: Option<PopoverMenuHandle<ContextMenu>>,
     attach: Option<Anchor>,
+    anchor: Option<Anchor>,
+    auto_flip: bool,
     offset: Option<Point<Pixels>>,
     tab_index: Option<isize>,
     chevron: bool,
+    searchable: bool,
+    on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    on_close: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    clearable: bool,
+    on_clear: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    badge: Option<SharedString>,
+    state: DropdownState,
 }
 
 impl DropdownMenu {
@@ -41,22 +162,76 @@ impl DropdownMenu {
         id: impl Into<ElementId>,
         label: impl Into<SharedString>,
         menu: Entity<ContextMenu>,
+    ) -> Self {
+        let bound_menu = menu.clone();
+        Self {
+            bound_menu: Some(bound_menu),
+            ..Self::new_with_menu_builder(id, label, move |_, _| menu.clone())
+        }
+    }
+
+    /// Builds the menu each time it is opened, so it can reflect state that changed
+    /// since the trigger was rendered.
+    fn new_with_menu_builder(
+        id: impl Into<ElementId>,
+        label: impl Into<SharedString>,
+        menu: impl Fn(&mut Window, &mut App) -> Entity<ContextMenu> + 'static,
+    ) -> Self {
+        Self::new_with_menu_source(id, label, MenuSource::Build(Rc::new(menu)))
+    }
+
+    /// Loads the menu the first time it's opened, such as from a list of models or branches
+    /// that has to be fetched, and reuses it for every later open.
+    ///
+    /// The trigger shows a spinner while the menu loads and opens it once it's ready. If
+    /// loading fails, the trigger shows the [error](DropdownState::Error), and opening it
+    /// again retries.
+    pub fn new_async(
+        id: impl Into<ElementId>,
+        label: impl Into<SharedString>,
+        loader: impl Fn(&mut Window, &mut App) -> Task<anyhow::Result<Entity<ContextMenu>>> + 'static,
+    ) -> Self {
+        Self::new_with_menu_source(id, label, MenuSource::Load(Rc::new(loader)))
+    }
+
+    fn new_with_menu_source(
+        id: impl Into<ElementId>,
+        label: impl Into<SharedString>,
+        menu: MenuSource,
     ) -> Self {
         Self {
             id: id.into(),
             label: LabelKind::Text(label.into()),
+            custom_trigger: None,
+            icon: None,
+            placeholder: None,
+            selected_label: None,
+            size: DropdownSize::default(),
             trigger_size: ButtonSize::Default,
             trigger_tooltip: None,
             trigger_icon: Some(IconName::ChevronUpDown),
             style: DropdownStyle::default(),
             menu,
+            bound_menu: None,
+            label_from_selection: false,
             full_width: false,
+            max_width: None,
             disabled: false,
+            disabled_message: None,
             handle: None,
             attach: None,
+            anchor: None,
+            auto_flip: false,
             offset: None,
             tab_index: None,
             chevron: true,
+            searchable: false,
+            on_open: None,
+            on_close: None,
+            clearable: false,
+            on_clear: None,
+            badge: None,
+            state: DropdownState::Ready,
         }
     }
 
@@ -68,26 +243,148 @@ impl DropdownMenu {
         Self {
             id: id.into(),
             label: LabelKind::Element(label),
+            custom_trigger: None,
+            icon: None,
+            placeholder: None,
+            selected_label: None,
+            size: DropdownSize::default(),
             trigger_size: ButtonSize::Default,
             trigger_tooltip: None,
             trigger_icon: Some(IconName::ChevronUpDown),
             style: DropdownStyle::default(),
-            menu,
+            bound_menu: Some(menu.clone()),
+            label_from_selection: false,
+            menu: MenuSource::Build(Rc::new(move |_, _| menu.clone())),
             full_width: false,
+            max_width: None,
             disabled: false,
+            disabled_message: None,
             handle: None,
             attach: None,
+            anchor: None,
+            auto_flip: false,
             offset: None,
             tab_index: None,
             chevron: true,
+            searchable: false,
+            on_open: None,
+            on_close: None,
+            clearable: false,
+            on_clear: None,
+            badge: None,
+            state: DropdownState::Ready,
         }
     }
 
+    /// Uses the caller's element as the whole trigger in place of the label and chevron, such
+    /// as an icon-only button in a toolbar.
+    ///
+    /// Options that only style the built-in trigger, like the icon, style, sizes and tooltip,
+    /// have no effect, so the trigger should carry its own tooltip. It is still disabled along
+    /// with the dropdown, including while it's [loading](DropdownState::Loading).
+    pub fn new_with_trigger(
+        id: impl Into<ElementId>,
+        trigger: impl PopoverTrigger + Disableable,
+        menu: Entity<ContextMenu>,
+    ) -> Self {
+        Self {
+            custom_trigger: Some(Box::new(move |popover, disabled| {
+                popover.trigger(trigger.disabled(disabled))
+            })),
+            ..Self::new(id, SharedString::default(), menu)
+        }
+    }
+
+    /// Builds a menu of titled groups of options, such as "Recent" and "All", with a header
+    /// above each group and a separator between them. Groups without options are skipped.
+    ///
+    /// The menu is built each time it's opened.
+    pub fn from_sections(
+        id: impl Into<ElementId>,
+        label: impl Into<SharedString>,
+        sections: Vec<(SharedString, Vec<DropdownItem>)>,
+    ) -> Self {
+        let sections = Rc::new(sections);
+        Self::new_with_menu_builder(id, label, move |window, cx| {
+            let sections = sections.clone();
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                let non_empty_sections = sections.iter().filter(|(_, items)| !items.is_empty());
+                for (ix, (title, items)) in non_empty_sections.enumerate() {
+                    if ix > 0 {
+                        menu = menu.separator();
+                    }
+                    menu = menu.header(title.clone());
+                    for item in items {
+                        let on_select = item.on_select.clone();
+                        menu = menu.item(
+                            ContextMenuEntry::new(item.label.clone())
+                                .when_some(item.icon, |entry, icon| entry.icon(icon))
+                                .handler(move |window, cx| on_select(window, cx)),
+                        );
+                    }
+                }
+                menu
+            })
+        })
+    }
+
+    /// Shows an icon before the label in the trigger.
+    pub fn icon(mut self, icon: IconName) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Text shown in a muted color while no [selected label](Self::selected_label) is set.
+    ///
+    /// Like the selected label, this takes the place of a text label and has no effect on
+    /// element labels.
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// The label of the current selection, shown in place of the text label or placeholder.
+    pub fn selected_label(mut self, label: impl Into<Option<SharedString>>) -> Self {
+        self.selected_label = label.into();
+        self
+    }
+
+    /// Takes the selected label, and the icon when none was set, from the menu's toggled-on
+    /// entry each time the trigger renders, so they can't drift out of sync with the menu.
+    ///
+    /// While no entry is toggled on, the text label or placeholder is shown as usual.
+    pub fn label_from_selection(mut self, label_from_selection: bool) -> Self {
+        self.label_from_selection = label_from_selection;
+        self
+    }
+
+    fn apply_menu_selection(&mut self, cx: &App) {
+        if !self.label_from_selection {
+            return;
+        }
+        let Some((label, icon)) = self
+            .bound_menu
+            .as_ref()
+            .and_then(|menu| menu.read(cx).checked_entry())
+        else {
+            return;
+        };
+        self.selected_label = Some(label);
+        self.icon = self.icon.or(icon);
+    }
+
     pub fn style(mut self, style: DropdownStyle) -> Self {
         self.style = style;
         self
     }
 
+    /// Scales the trigger's padding, label and icons together.
+    pub fn size(mut self, size: DropdownSize) -> Self {
+        self.size = size;
+        self.trigger_size = size.button_size();
+        self
+    }
+
     pub fn trigger_size(mut self, size: ButtonSize) -> Self {
         self.trigger_size = size;
         self
@@ -111,6 +408,13 @@ impl DropdownMenu {
         self
     }
 
+    /// Caps the trigger's width. Text labels that don't fit are truncated with an ellipsis,
+    /// while the chevron stays visible.
+    pub fn max_width(mut self, max_width: Pixels) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
     pub fn handle(mut self, handle: PopoverMenuHandle<ContextMenu>) -> Self {
         self.handle = Some(handle);
         self
@@ -122,12 +426,27 @@ impl DropdownMenu {
         self
     }
 
+    /// Defines which corner of the menu to anchor to the attachment point.
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// Opens the menu upward instead when it doesn't fit below the trigger and there's more
+    /// room above it.
+    pub fn auto_flip(mut self, auto_flip: bool) -> Self {
+        self.auto_flip = auto_flip;
+        self
+    }
+
     /// Offsets the position of the menu by that many pixels.
     pub fn offset(mut self, offset: Point<Pixels>) -> Self {
         self.offset = Some(offset);
         self
     }
 
+    /// Makes the trigger a tab stop at this index, so it can be focused with Tab and opened
+    /// with Enter or Space. Without one, the trigger isn't reachable from the keyboard.
     pub fn tab_index(mut self, arg: isize) -> Self {
         self.tab_index = Some(arg);
         self
@@ -137,6 +456,56 @@ impl DropdownMenu {
         self.chevron = false;
         self
     }
+
+    /// Lets the user filter the menu's entries by typing once it's open.
+    pub fn searchable(mut self, searchable: bool) -> Self {
+        self.searchable = searchable;
+        self
+    }
+
+    /// Called each time the menu is opened, before it paints.
+    pub fn on_open(mut self, on_open: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_open = Some(Rc::new(on_open));
+        self
+    }
+
+    /// Called each time the menu is dismissed.
+    pub fn on_close(mut self, on_close: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_close = Some(Rc::new(on_close));
+        self
+    }
+
+    /// Shows a button in the trigger that clears the current selection, as long as
+    /// there is a [`selected_label`](Self::selected_label) and an [`on_clear`](Self::on_clear) handler.
+    pub fn clearable(mut self, clearable: bool) -> Self {
+        self.clearable = clearable;
+        self
+    }
+
+    /// Shows a small pill between the label and the chevron, such as the number of active
+    /// filters. Nothing is shown for empty text.
+    pub fn badge(mut self, badge: impl Into<SharedString>) -> Self {
+        self.badge = Some(badge.into()).filter(|badge| !badge.is_empty());
+        self
+    }
+
+    /// Explains why the dropdown is [disabled](Disableable::disabled), shown as the trigger's
+    /// tooltip in place of any other while it is.
+    pub fn disabled_message(mut self, message: impl Into<SharedString>) -> Self {
+        self.disabled_message = Some(message.into());
+        self
+    }
+
+    pub fn state(mut self, state: DropdownState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Called when the clear button is clicked. The click doesn't open the menu.
+    pub fn on_clear(mut self, on_clear: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_clear = Some(Rc::new(on_clear));
+        self
+    }
 }
 
 impl Disableable for DropdownMenu {
@@ -147,7 +516,42 @@ impl Disableable for DropdownMenu {
 }
 
 impl RenderOnce for DropdownMenu {
-    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+    fn render(mut self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let (menu_builder, async_menu): (
+            Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<ContextMenu>>>,
+            _,
+        ) = match &self.menu {
+            MenuSource::Build(menu) => {
+                let menu = menu.clone();
+                (Rc::new(move |window, cx| Some(menu(window, cx))), None)
+            }
+            MenuSource::Load(loader) => {
+                let async_menu =
+                    window.use_keyed_state((self.id.clone(), "async-menu"), cx, |_, _| {
+                        AsyncMenu::default()
+                    });
+                // The menu is opened through the handle once it has loaded.
+                let handle = self.handle.get_or_insert_default().clone();
+                let loader = loader.clone();
+                let menu_builder = {
+                    let async_menu = async_menu.clone();
+                    Rc::new(move |window: &mut Window, cx: &mut App| {
+                        open_async_menu(&loader, &async_menu, handle.clone(), window, cx)
+                    })
+                };
+                (menu_builder, Some(async_menu))
+            }
+        };
+        if let Some(async_menu) = async_menu {
+            match async_menu.read(cx) {
+                AsyncMenu::NotLoaded => {}
+                AsyncMenu::Loading { .. } => self.state = DropdownState::Loading,
+                AsyncMenu::Loaded(menu) => self.bound_menu = Some(menu.clone()),
+                AsyncMenu::Failed(message) => self.state = DropdownState::Error(message.clone()),
+            }
+        }
+
+        self.apply_menu_selection(cx);
         let button_style = match self.style {
             DropdownStyle::Solid => ButtonStyle::Filled,
             DropdownStyle::Subtle => ButtonStyle::Subtle,
@@ -155,61 +559,193 @@ impl RenderOnce for DropdownMenu {
             DropdownStyle::Ghost => ButtonStyle::Transparent,
         };
 
+        let loading = self.state == DropdownState::Loading;
+        let disabled = self.disabled || loading;
+        let tab_index = self.tab_index.filter(|_| !disabled);
+        // A trigger with a tab index keeps a focus handle across renders, so Enter and Space
+        // open the menu, which then takes focus so the arrow keys and Escape work, and hands it
+        // back to the trigger on close.
+        let focus_handle = tab_index.map(|tab_index| {
+            window
+                .use_keyed_state((self.id.clone(), "focus"), cx, |_, cx| cx.focus_handle())
+                .read(cx)
+                .clone()
+                .tab_stop(true)
+                .tab_index(tab_index)
+        });
+        let (error, trigger_tooltip) = match self.state {
+            DropdownState::Error(message) => (
+                true,
+                Some(Box::new(Tooltip::text(message))
+                    as Box<dyn Fn(&mut Window, &mut App) -> AnyView>),
+            ),
+            DropdownState::Ready | DropdownState::Loading => (false, self.trigger_tooltip),
+        };
+        let trigger_tooltip =
+            match self.disabled_message.filter(|_| self.disabled) {
+                Some(message) => Some(Box::new(Tooltip::text(message))
+                    as Box<dyn Fn(&mut Window, &mut App) -> AnyView>),
+                None => trigger_tooltip,
+            };
         let full_width = self.full_width;
         let trigger_size = self.trigger_size;
+        let icon_size = self.size.icon_size();
+        let leading_icon = self.icon.map(|icon| {
+            Icon::new(icon).size(icon_size).color(if disabled {
+                Color::Disabled
+            } else {
+                Color::Muted
+            })
+        });
+
+        let clear_button = self
+            .on_clear
+            .filter(|_| self.clearable && self.selected_label.is_some() && !disabled)
+            .map(|on_clear| {
+                IconButton::new((self.id.clone(), "clear"), IconName::Close)
+                    .icon_size(icon_size)
+                    .icon_color(Color::Muted)
+                    .tooltip(Tooltip::text("Clear"))
+                    .on_click(move |_, window, cx| on_clear(window, cx))
+            });
+        let badge = self.badge.map(|badge| {
+            h_flex()
+                .px_1()
+                .rounded_full()
+                .bg(cx.theme().colors().text_accent.opacity(0.15))
+                .child(
+                    Label::new(badge)
+                        .size(LabelSize::XSmall)
+                        .color(if disabled {
+                            Color::Disabled
+                        } else {
+                            Color::Accent
+                        }),
+                )
+        });
+        let trigger_icon = self.trigger_icon.filter(|_| self.chevron);
+        let truncate = full_width || self.max_width.is_some();
+        let label_size = self.size.label_size();
+        let selected_label = self.selected_label;
+        let placeholder = self.placeholder;
+        let label_text = move |text| {
+            let (text, color) = match (selected_label, placeholder) {
+                (Some(selected_label), _) => (selected_label, Color::Default),
+                (None, Some(placeholder)) => (placeholder, Color::Placeholder),
+                (None, None) => (text, Color::Default),
+            };
+            (text, if error { Color::Error } else { color })
+        };
+        let chevron_color = if error { Color::Error } else { Color::Muted };
 
         let (text_button, element_button) = match self.label {
-            LabelKind::Text(text) => (
-                Some(
-                    Button::new(self.id.clone(), text)
-                        .style(button_style)
-                        .when_some(self.trigger_icon.filter(|_| self.chevron), |this, icon| {
-                            this.end_icon(
-                                Icon::new(icon).size(IconSize::XSmall).color(Color::Muted),
-                            )
-                        })
-                        .when(full_width, |this| this.full_width())
-                        .size(trigger_size)
-                        .disabled(self.disabled)
-                        .when_some(self.tab_index, |this, tab_index| this.tab_index(tab_index)),
-                ),
-                None,
-            ),
-            LabelKind::Element(element) => (
-                None,
-                Some(
-                    ButtonLike::new(self.id.clone())
-                        .child(element)
-                        .style(button_style)
-                        .when(self.chevron, |this| {
-                            this.child(
-                                Icon::new(IconName::ChevronUpDown)
-                                    .size(IconSize::XSmall)
-                                    .color(Color::Muted),
-                            )
-                        })
-                        .when(full_width, |this| this.full_width())
-                        .size(trigger_size)
-                        .disabled(self.disabled)
-                        .when_some(self.tab_index, |this, tab_index| this.tab_index(tab_index)),
-                ),
-            ),
+            LabelKind::Text(text) if clear_button.is_none() && badge.is_none() && !loading => {
+                let (text, color) = label_text(text);
+                (
+                    Some(
+                        Button::new(self.id.clone(), text)
+                            .color(color)
+                            .label_size(label_size)
+                            .style(button_style)
+                            .start_icon(leading_icon)
+                            .when_some(trigger_icon, |this, icon| {
+                                this.end_icon(Icon::new(icon).size(icon_size).color(chevron_color))
+                            })
+                            .when(full_width, |this| this.full_width())
+                            .when_some(self.max_width, |this, max_width| this.max_width(max_width))
+                            .truncate(truncate)
+                            .size(trigger_size)
+                            .disabled(disabled)
+                            .when_some(focus_handle.as_ref(), |this, focus_handle| {
+                                this.track_focus(focus_handle)
+                            })
+                            .when_some(tab_index, |this, tab_index| this.tab_index(tab_index)),
+                    ),
+                    None,
+                )
+            }
+            label => {
+                let element = match label {
+                    LabelKind::Text(text) => {
+                        let (text, color) = label_text(text);
+                        Label::new(text)
+                            .size(label_size)
+                            .color(color)
+                            .when(truncate, |this| this.truncate())
+                            .into_any_element()
+                    }
+                    LabelKind::Element(element) => element,
+                };
+                (
+                    None,
+                    Some(
+                        ButtonLike::new(self.id.clone())
+                            .children(leading_icon)
+                            .child(element)
+                            .children(badge)
+                            .children(clear_button)
+                            .style(button_style)
+                            .map(|this| {
+                                if loading {
+                                    this.child(
+                                        Icon::new(IconName::LoadCircle)
+                                            .size(icon_size)
+                                            .color(Color::Muted)
+                                            .with_keyed_rotate_animation(
+                                                (self.id.clone(), "loading"),
+                                                2,
+                                            ),
+                                    )
+                                } else {
+                                    this.when_some(trigger_icon, |this, icon| {
+                                        this.child(
+                                            Icon::new(icon).size(icon_size).color(chevron_color),
+                                        )
+                                    })
+                                }
+                            })
+                            .when(full_width, |this| this.full_width())
+                            .when_some(self.max_width, |this, max_width| this.max_width(max_width))
+                            .size(trigger_size)
+                            .disabled(disabled)
+                            .when_some(focus_handle.as_ref(), |this, focus_handle| {
+                                this.track_focus(focus_handle)
+                            })
+                            .when_some(tab_index, |this, tab_index| this.tab_index(tab_index)),
+                    ),
+                )
+            }
         };
 
+        let searchable = self.searchable;
         let mut popover = PopoverMenu::new((self.id.clone(), "popover"))
             .full_width(self.full_width)
-            .menu(move |_window, _cx| Some(self.menu.clone()));
+            .when_some(self.on_open, |popover, on_open| popover.on_open(on_open))
+            .when_some(self.on_close, |popover, on_close| {
+                popover.on_close(on_close)
+            })
+            .menu(move |window, cx| {
+                let menu = menu_builder(window, cx)?;
+                if searchable {
+                    menu.update(cx, |menu, cx| menu.set_searchable(true, cx));
+                }
+                Some(menu)
+            });
 
-        popover = match (text_button, element_button, self.trigger_tooltip) {
-            (Some(text_button), None, Some(tooltip)) => {
-                popover.trigger_with_tooltip(text_button, tooltip)
+        popover = if let Some(custom_trigger) = self.custom_trigger {
+            custom_trigger(popover, disabled)
+        } else {
+            match (text_button, element_button, trigger_tooltip) {
+                (Some(text_button), None, Some(tooltip)) => {
+                    popover.trigger_with_tooltip(text_button, tooltip)
+                }
+                (Some(text_button), None, None) => popover.trigger(text_button),
+                (None, Some(element_button), Some(tooltip)) => {
+                    popover.trigger_with_tooltip(element_button, tooltip)
+                }
+                (None, Some(element_button), None) => popover.trigger(element_button),
+                _ => popover,
             }
-            (Some(text_button), None, None) => popover.trigger(text_button),
-            (None, Some(element_button), Some(tooltip)) => {
-                popover.trigger_with_tooltip(element_button, tooltip)
-            }
-            (None, Some(element_button), None) => popover.trigger(element_button),
-            _ => popover,
         };
 
         popover
@@ -217,11 +753,184 @@ impl RenderOnce for DropdownMenu {
                 Some(attach) => attach,
                 None => Anchor::BottomRight,
             })
+            .when_some(self.anchor, |this, anchor| this.anchor(anchor))
+            .auto_flip(self.auto_flip)
             .when_some(self.offset, |this, offset| this.offset(offset))
             .when_some(self.handle, |this, handle| this.with_handle(handle))
     }
 }
 
+/// Returns the loaded menu, or starts loading it and returns `None` so the menu stays closed
+/// until it's ready, when it's opened through `handle`.
+fn open_async_menu(
+    loader: &MenuLoader,
+    async_menu: &Entity<AsyncMenu>,
+    handle: PopoverMenuHandle<ContextMenu>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Option<Entity<ContextMenu>> {
+    match async_menu.read(cx) {
+        AsyncMenu::Loaded(menu) => return Some(menu.clone()),
+        AsyncMenu::Loading { .. } => return None,
+        AsyncMenu::NotLoaded | AsyncMenu::Failed(_) => {}
+    }
+
+    let load = loader(window, cx);
+    let loading = window.spawn(cx, {
+        let async_menu = async_menu.clone();
+        async move |cx| {
+            let result = load.await;
+            cx.update(|window, cx| {
+                let loaded = result.is_ok();
+                async_menu.update(cx, |async_menu, cx| {
+                    *async_menu = match result {
+                        Ok(menu) => AsyncMenu::Loaded(menu),
+                        Err(error) => AsyncMenu::Failed(error.to_string().into()),
+                    };
+                    cx.notify();
+                });
+                if loaded {
+                    handle.show(window, cx);
+                }
+            })
+            .ok();
+        }
+    });
+    async_menu.update(cx, |async_menu, cx| {
+        *async_menu = AsyncMenu::Loading { _task: loading };
+        cx.notify();
+    });
+    None
+}
+
+/// A dropdown that lets the user pick any number of options.
+///
+/// Selected options show a checkmark, and toggling one keeps the menu open.
+/// The trigger summarizes the selection: a single option shows its label,
+/// several show "N selected", or a comma-joined list when a `max_width` is set
+/// to truncate it.
+pub struct MultiSelectDropdown {
+    id: ElementId,
+    placeholder: SharedString,
+    options: Vec<SharedString>,
+    selected: Vec<SharedString>,
+    max_width: Option<Pixels>,
+    on_toggle: Option<Rc<dyn Fn(&SharedString, bool, &mut Window, &mut App)>>,
+}
+
+impl MultiSelectDropdown {
+    pub fn new(
+        id: impl Into<ElementId>,
+        placeholder: impl Into<SharedString>,
+        options: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            placeholder: placeholder.into(),
+            options: options.into_iter().map(Into::into).collect(),
+            selected: Vec::new(),
+            max_width: None,
+            on_toggle: None,
+        }
+    }
+
+    pub fn selected(mut self, selected: impl IntoIterator<Item = impl Into<SharedString>>) -> Self {
+        self.selected = selected.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn max_width(mut self, max_width: Pixels) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Called with the option and whether it is now selected.
+    pub fn on_toggle(
+        mut self,
+        on_toggle: impl Fn(&SharedString, bool, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_toggle = Some(Rc::new(on_toggle));
+        self
+    }
+
+    pub fn selection(&self) -> &[SharedString] {
+        &self.selected
+    }
+
+    pub fn toggle(&mut self, option: &SharedString, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = match self.selected.iter().position(|selected| selected == option) {
+            Some(index) => {
+                self.selected.remove(index);
+                false
+            }
+            None => {
+                self.selected.push(option.clone());
+                true
+            }
+        };
+        if let Some(on_toggle) = self.on_toggle.as_ref() {
+            on_toggle(option, selected, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn summary(&self) -> Option<SharedString> {
+        let mut selected_options = self
+            .options
+            .iter()
+            .filter(|option| self.selected.contains(option));
+        let first = selected_options.next()?;
+        if self.selected.len() == 1 {
+            Some(first.clone())
+        } else if self.max_width.is_some() {
+            let mut summary = first.to_string();
+            for option in selected_options {
+                summary.push_str(", ");
+                summary.push_str(option);
+            }
+            Some(summary.into())
+        } else {
+            Some(format!("{} selected", self.selected.len()).into())
+        }
+    }
+}
+
+impl Render for MultiSelectDropdown {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let this = cx.entity();
+        DropdownMenu::new_with_menu_builder(
+            self.id.clone(),
+            self.placeholder.clone(),
+            move |window, cx| {
+                let this = this.clone();
+                ContextMenu::build_persistent(window, cx, move |mut menu, _, cx| {
+                    let dropdown = this.read(cx);
+                    for option in dropdown.options.clone() {
+                        let toggled = dropdown.selected.contains(&option);
+                        menu = menu.toggleable_entry(
+                            option.clone(),
+                            toggled,
+                            IconPosition::Start,
+                            None,
+                            {
+                                let this = this.downgrade();
+                                move |window, cx| {
+                                    this.update(cx, |this, cx| this.toggle(&option, window, cx))
+                                        .ok();
+                                }
+                            },
+                        );
+                    }
+                    menu
+                })
+            },
+        )
+        .placeholder(self.placeholder.clone())
+        .selected_label(self.summary())
+        .when_some(self.max_width, |this, max_width| this.max_width(max_width))
+    }
+}
+
 impl Component for DropdownMenu {
     fn scope() -> ComponentScope {
         ComponentScope::Input
@@ -280,14 +989,228 @@ impl Component for DropdownMenu {
                             DropdownMenu::new("default", "Select an option", menu.clone())
                                 .into_any_element(),
                         ),
+                        single_example(
+                            "With Icon",
+                            DropdownMenu::new("with-icon", "Rust", menu.clone())
+                                .icon(IconName::FileRust)
+                                .into_any_element(),
+                        ),
                         single_example(
                             "Full Width",
                             DropdownMenu::new("full-width", "Full Width Dropdown", menu.clone())
                                 .full_width(true)
                                 .into_any_element(),
                         ),
+                        single_example(
+                            "Custom Trigger",
+                            DropdownMenu::new_with_trigger(
+                                "custom-trigger",
+                                IconButton::new("custom-trigger-button", IconName::Ellipsis)
+                                    .tooltip(Tooltip::text("More Options")),
+                                menu.clone(),
+                            )
+                            .into_any_element(),
+                        ),
+                        single_example(
+                            "Max Width",
+                            DropdownMenu::new(
+                                "max-width",
+                                "A very long label that does not fit in the trigger",
+                                menu.clone(),
+                            )
+                            .max_width(px(160.))
+                            .into_any_element(),
+                        ),
                     ],
                 ),
+                example_group_with_title(
+                    "Sections",
+                    vec![single_example(
+                        "Recent and All",
+                        DropdownMenu::from_sections(
+                            "sections",
+                            "Open Project",
+                            vec![
+                                (
+                                    "Recent".into(),
+                                    vec![
+                                        DropdownItem::new("zed", |_, _| {}).icon(IconName::Folder),
+                                        DropdownItem::new("gpui", |_, _| {}).icon(IconName::Folder),
+                                    ],
+                                ),
+                                (
+                                    "All".into(),
+                                    vec![
+                                        DropdownItem::new("gpui", |_, _| {}),
+                                        DropdownItem::new("tree-sitter", |_, _| {}),
+                                        DropdownItem::new("zed", |_, _| {}),
+                                    ],
+                                ),
+                            ],
+                        )
+                        .into_any_element(),
+                    )],
+                ),
+                example_group_with_title(
+                    "Async",
+                    vec![
+                        single_example(
+                            "Load on Open",
+                            DropdownMenu::new_async("async", "Select a model", |window, cx| {
+                                window.spawn(cx, async move |cx| {
+                                    cx.background_executor()
+                                        .timer(std::time::Duration::from_secs(1))
+                                        .await;
+                                    cx.update(|window, cx| {
+                                        ContextMenu::build(window, cx, |menu, _, _| {
+                                            menu.entry("Fast", None, |_, _| {})
+                                                .entry("Balanced", None, |_, _| {})
+                                                .entry("Thorough", None, |_, _| {})
+                                        })
+                                    })
+                                })
+                            })
+                            .into_any_element(),
+                        ),
+                        single_example(
+                            "Failed Load",
+                            DropdownMenu::new_async("async-failed", "Select a model", |_, _| {
+                                Task::ready(Err(anyhow::anyhow!("Couldn't reach the server")))
+                            })
+                            .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Placeholder",
+                    vec![
+                        single_example(
+                            "Empty",
+                            DropdownMenu::new("placeholder-empty", "Branch", menu.clone())
+                                .placeholder("Select a branch…")
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Selected",
+                            DropdownMenu::new("placeholder-selected", "Branch", menu.clone())
+                                .placeholder("Select a branch…")
+                                .selected_label(SharedString::from("main"))
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Multi-Select",
+                    vec![single_example(
+                        "Formatters",
+                        window
+                            .use_keyed_state("multi-select", cx, |_, _| {
+                                MultiSelectDropdown::new(
+                                    "multi-select",
+                                    "Select formatters…",
+                                    ["Prettier", "Language Server", "External"],
+                                )
+                                .selected(["Prettier"])
+                            })
+                            .into_any_element(),
+                    )],
+                ),
+                example_group_with_title(
+                    "Clearable",
+                    vec![
+                        single_example(
+                            "Selected",
+                            DropdownMenu::new("clearable-selected", "Theme", menu.clone())
+                                .placeholder("System default")
+                                .selected_label(SharedString::from("One Dark"))
+                                .clearable(true)
+                                .on_clear(|_, _| {})
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Empty",
+                            DropdownMenu::new("clearable-empty", "Theme", menu.clone())
+                                .placeholder("System default")
+                                .clearable(true)
+                                .on_clear(|_, _| {})
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Badge",
+                    vec![
+                        single_example(
+                            "With Badge",
+                            DropdownMenu::new("badge", "Filters", menu.clone())
+                                .badge("3")
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Empty Badge",
+                            DropdownMenu::new("badge-empty", "Filters", menu.clone())
+                                .badge("")
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Full Width",
+                            DropdownMenu::new("badge-full-width", "Filters", menu.clone())
+                                .badge("12")
+                                .full_width(true)
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Search",
+                    vec![single_example(
+                        "Searchable",
+                        DropdownMenu::new("searchable", "Select an option", menu.clone())
+                            .searchable(true)
+                            .into_any_element(),
+                    )],
+                ),
+                example_group_with_title(
+                    "Placement",
+                    vec![single_example(
+                        "Window Quadrants",
+                        div()
+                            .relative()
+                            .w(px(480.))
+                            .h(px(200.))
+                            .border_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(
+                                div().absolute().top_2().left_2().child(
+                                    DropdownMenu::new("top-left", "Top Left", menu.clone())
+                                        .attach(Anchor::BottomLeft),
+                                ),
+                            )
+                            .child(
+                                div().absolute().top_2().right_2().child(
+                                    DropdownMenu::new("top-right", "Top Right", menu.clone())
+                                        .attach(Anchor::BottomRight)
+                                        .anchor(Anchor::TopRight),
+                                ),
+                            )
+                            .child(
+                                div().absolute().bottom_2().left_2().child(
+                                    DropdownMenu::new("bottom-left", "Bottom Left", menu.clone())
+                                        .attach(Anchor::TopLeft)
+                                        .anchor(Anchor::BottomLeft),
+                                ),
+                            )
+                            .child(
+                                div().absolute().bottom_2().right_2().child(
+                                    DropdownMenu::new("bottom-right", "Auto Flip", menu.clone())
+                                        .attach(Anchor::BottomRight)
+                                        .anchor(Anchor::TopRight)
+                                        .auto_flip(true),
+                                ),
+                            )
+                            .into_any_element(),
+                    )],
+                ),
                 example_group_with_title(
                     "Submenus",
                     vec![single_example(
@@ -299,6 +1222,18 @@ impl Component for DropdownMenu {
                 example_group_with_title(
                     "Styles",
                     vec![
+                        single_example(
+                            "Solid",
+                            DropdownMenu::new("solid", "Solid Dropdown", menu.clone())
+                                .style(DropdownStyle::Solid)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Subtle",
+                            DropdownMenu::new("subtle", "Subtle Dropdown", menu.clone())
+                                .style(DropdownStyle::Subtle)
+                                .into_any_element(),
+                        ),
                         single_example(
                             "Outlined",
                             DropdownMenu::new("outlined", "Outlined Dropdown", menu.clone())
@@ -313,16 +1248,317 @@ impl Component for DropdownMenu {
                         ),
                     ],
                 ),
+                example_group_with_title(
+                    "Sizes",
+                    vec![
+                        single_example(
+                            "Small",
+                            DropdownMenu::new("size-small", "Small", menu.clone())
+                                .size(DropdownSize::Small)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Medium",
+                            DropdownMenu::new("size-medium", "Medium", menu.clone())
+                                .size(DropdownSize::Medium)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Large",
+                            DropdownMenu::new("size-large", "Large", menu.clone())
+                                .size(DropdownSize::Large)
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Loading and Errors",
+                    vec![
+                        single_example(
+                            "Ready",
+                            DropdownMenu::new("state-ready", "main", menu.clone())
+                                .state(DropdownState::Ready)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Loading",
+                            DropdownMenu::new("state-loading", "Fetching branches…", menu.clone())
+                                .state(DropdownState::Loading)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Error",
+                            DropdownMenu::new("state-error", "Branches unavailable", menu.clone())
+                                .state(DropdownState::Error(
+                                    "Failed to fetch remote branches".into(),
+                                ))
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
+                example_group_with_title(
+                    "Disabled Styles",
+                    vec![
+                        single_example(
+                            "Solid",
+                            DropdownMenu::new("disabled-solid", "Solid", menu.clone())
+                                .style(DropdownStyle::Solid)
+                                .disabled(true)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Outlined",
+                            DropdownMenu::new("disabled-outlined", "Outlined", menu.clone())
+                                .style(DropdownStyle::Outlined)
+                                .disabled(true)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Ghost",
+                            DropdownMenu::new("disabled-ghost", "Ghost", menu.clone())
+                                .style(DropdownStyle::Ghost)
+                                .disabled(true)
+                                .into_any_element(),
+                        ),
+                    ],
+                ),
                 example_group_with_title(
                     "States",
-                    vec![single_example(
-                        "Disabled",
-                        DropdownMenu::new("disabled", "Disabled Dropdown", menu)
-                            .disabled(true)
-                            .into_any_element(),
-                    )],
+                    vec![
+                        single_example(
+                            "Disabled",
+                            DropdownMenu::new("disabled", "Disabled Dropdown", menu.clone())
+                                .disabled(true)
+                                .into_any_element(),
+                        ),
+                        single_example(
+                            "Disabled With Reason",
+                            DropdownMenu::new("disabled-with-reason", "Model", menu)
+                                .disabled(true)
+                                .disabled_message("Sign in to choose a model")
+                                .into_any_element(),
+                        ),
+                    ],
                 ),
             ])
             .into_any_element()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use gpui::{Font, KeyUpEvent, Keystroke, TestAppContext, font};
+
+    use super::*;
+
+    struct TestThemeSettings(Font);
+
+    impl theme::ThemeSettingsProvider for TestThemeSettings {
+        fn ui_font<'a>(&'a self, _: &'a App) -> &'a Font {
+            &self.0
+        }
+
+        fn buffer_font<'a>(&'a self, _: &'a App) -> &'a Font {
+            &self.0
+        }
+
+        fn ui_font_size(&self, _: &App) -> Pixels {
+            px(16.)
+        }
+
+        fn buffer_font_size(&self, _: &App) -> Pixels {
+            px(14.)
+        }
+
+        fn ui_density(&self, _: &App) -> theme::UiDensity {
+            theme::UiDensity::Default
+        }
+    }
+
+    struct DropdownView {
+        menu: Entity<ContextMenu>,
+        handle: PopoverMenuHandle<ContextMenu>,
+        tab_index: Option<isize>,
+        disabled: bool,
+    }
+
+    impl Render for DropdownView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                DropdownMenu::new("dropdown", "Indent", self.menu.clone())
+                    .handle(self.handle.clone())
+                    .when_some(self.tab_index, |this, tab_index| this.tab_index(tab_index))
+                    .disabled(self.disabled),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn focused_trigger_opens_the_menu_on_enter(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            theme::init(theme::LoadThemes::JustBase, cx);
+            theme::set_theme_settings_provider(
+                Box::new(TestThemeSettings(font(".SystemUIFont"))),
+                cx,
+            );
+        });
+        let (view, cx) = cx.add_window_view(|window, cx| DropdownView {
+            menu: ContextMenu::build(window, cx, |menu, _, _| {
+                menu.entry("Spaces", None, |_, _| {})
+            }),
+            handle: PopoverMenuHandle::default(),
+            tab_index: None,
+            disabled: false,
+        });
+        let handle = view.read_with(cx, |view, _| view.handle.clone());
+        let press_enter = |cx: &mut gpui::VisualTestContext| {
+            cx.simulate_keystrokes("enter");
+            cx.simulate_event(KeyUpEvent {
+                keystroke: Keystroke::parse("enter").expect("valid keystroke"),
+            });
+        };
+
+        let tab_reaches_trigger = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, cx| window.focus_next(cx));
+            cx.run_until_parked();
+            cx.update(|window, cx| window.focused(cx).is_some())
+        };
+        let update_view = |cx: &mut gpui::VisualTestContext, update: fn(&mut DropdownView)| {
+            view.update(cx, |view, cx| {
+                update(view);
+                cx.notify();
+            });
+            cx.run_until_parked();
+        };
+
+        assert!(
+            !tab_reaches_trigger(cx),
+            "a trigger without a tab index isn't a tab stop"
+        );
+
+        update_view(cx, |view| {
+            view.tab_index = Some(0);
+            view.disabled = true;
+        });
+        assert!(
+            !tab_reaches_trigger(cx),
+            "a disabled trigger isn't a tab stop"
+        );
+
+        update_view(cx, |view| view.disabled = false);
+        assert!(tab_reaches_trigger(cx));
+        cx.run_until_parked();
+        assert!(!handle.is_deployed());
+        press_enter(cx);
+        assert!(handle.is_deployed());
+
+        cx.update(|_, cx| handle.hide(cx));
+        cx.run_until_parked();
+        assert!(!handle.is_deployed());
+        press_enter(cx);
+        assert!(
+            handle.is_deployed(),
+            "focus returns to the trigger on close"
+        );
+    }
+
+    #[gpui::test]
+    fn label_from_selection_follows_the_menu(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let selected = Rc::new(Cell::new(None));
+        let menu = cx.update(|window, cx| {
+            let selected = selected.clone();
+            ContextMenu::build_persistent(window, cx, move |mut context_menu, _, _| {
+                for (ix, label) in ["Spaces", "Tabs"].into_iter().enumerate() {
+                    let selected = selected.clone();
+                    context_menu = context_menu.toggleable_entry(
+                        label,
+                        selected.get() == Some(ix),
+                        IconPosition::End,
+                        None,
+                        move |_, _| selected.set(Some(ix)),
+                    );
+                }
+                context_menu
+            })
+        });
+        let displayed_label = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|_, cx| {
+                let mut dropdown = DropdownMenu::new("indent", "Indent", menu.clone())
+                    .placeholder("Choose indentation")
+                    .label_from_selection(true);
+                dropdown.apply_menu_selection(cx);
+                dropdown.selected_label
+            })
+        };
+
+        assert_eq!(displayed_label(cx), None);
+
+        menu.update_in(cx, |context_menu, window, cx| {
+            context_menu.select_last(window, cx);
+            context_menu.confirm(&menu::Confirm, window, cx);
+        });
+        assert_eq!(displayed_label(cx), Some("Tabs".into()));
+
+        menu.update_in(cx, |context_menu, window, cx| {
+            context_menu.select_first(&menu::SelectFirst, window, cx);
+            context_menu.confirm(&menu::Confirm, window, cx);
+        });
+        assert_eq!(displayed_label(cx), Some("Spaces".into()));
+
+        let label = cx.update(|_, cx| {
+            let mut dropdown = DropdownMenu::new("indent", "Indent", menu.clone());
+            dropdown.apply_menu_selection(cx);
+            dropdown.selected_label
+        });
+        assert_eq!(label, None, "selection is only applied when enabled");
+    }
+
+    #[gpui::test]
+    fn async_menu_loads_once_and_is_reused(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let loads = Rc::new(Cell::new(0));
+        let fail = Rc::new(Cell::new(true));
+        let loader: MenuLoader = Rc::new({
+            let loads = loads.clone();
+            let fail = fail.clone();
+            move |window, cx| {
+                loads.set(loads.get() + 1);
+                if fail.get() {
+                    return Task::ready(Err(anyhow::anyhow!("offline")));
+                }
+                let menu = ContextMenu::build(window, cx, |menu, _, _| {
+                    menu.entry("main", None, |_, _| {})
+                });
+                Task::ready(Ok(menu))
+            }
+        });
+        let async_menu = cx.update(|_, cx| cx.new(|_| AsyncMenu::default()));
+        let handle = PopoverMenuHandle::default();
+        let open = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, cx| {
+                open_async_menu(&loader, &async_menu, handle.clone(), window, cx)
+            })
+        };
+
+        assert!(open(cx).is_none());
+        cx.update(|_, cx| {
+            assert!(matches!(async_menu.read(cx), AsyncMenu::Loading { .. }));
+        });
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            assert!(
+                matches!(async_menu.read(cx), AsyncMenu::Failed(message) if message == "offline")
+            );
+        });
+
+        fail.set(false);
+        assert!(open(cx).is_none(), "a failed load is retried");
+        cx.run_until_parked();
+        let menu = open(cx).expect("the menu has loaded");
+        assert_eq!(open(cx), Some(menu));
+        assert_eq!(loads.get(), 2);
+    }
+}
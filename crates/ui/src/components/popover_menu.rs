@@ -1,10 +1,11 @@
 use std::{cell::RefCell, rc::Rc};
 
 use gpui::{
-    Anchor, AnyElement, AnyView, App, Bounds, DismissEvent, DispatchPhase, Element, ElementId,
-    Entity, Focusable as _, GlobalElementId, HitboxBehavior, HitboxId, InteractiveElement,
-    IntoElement, LayoutId, Length, ManagedView, MouseDownEvent, ParentElement, Pixels, Point,
-    Style, Window, anchored, deferred, div, point, prelude::FluentBuilder, px, size,
+    Anchor, AnyElement, AnyView, App, Axis, Bounds, DismissEvent, DispatchPhase, Element,
+    ElementId, Entity, Focusable as _, GlobalElementId, HitboxBehavior, HitboxId,
+    InteractiveElement, IntoElement, LayoutId, Length, ManagedView, MouseDownEvent, ParentElement,
+    Pixels, Point, Style, Subscription, Window, anchored, deferred, div, point,
+    prelude::FluentBuilder, px, size,
 };
 
 use crate::prelude::*;
@@ -56,6 +57,7 @@ struct PopoverMenuHandleState<M> {
     menu_builder: Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<M>>>,
     menu: Rc<RefCell<Option<Entity<M>>>>,
     on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    on_close: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
 }
 
 impl<M: ManagedView> PopoverMenuHandle<M> {
@@ -65,6 +67,7 @@ impl<M: ManagedView> PopoverMenuHandle<M> {
                 &state.menu_builder,
                 &state.menu,
                 state.on_open.clone(),
+                state.on_close.clone(),
                 window,
                 cx,
             );
@@ -142,7 +145,9 @@ pub struct PopoverMenu<M: ManagedView> {
     offset: Option<Point<Pixels>>,
     trigger_handle: Option<PopoverMenuHandle<M>>,
     on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    on_close: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
     full_width: bool,
+    auto_flip: bool,
 }
 
 impl<M: ManagedView> PopoverMenu<M> {
@@ -157,7 +162,9 @@ impl<M: ManagedView> PopoverMenu<M> {
             offset: None,
             trigger_handle: None,
             on_open: None,
+            on_close: None,
             full_width: false,
+            auto_flip: false,
         }
     }
 
@@ -181,12 +188,20 @@ impl<M: ManagedView> PopoverMenu<M> {
 
     pub fn trigger<T: PopoverTrigger>(mut self, t: T) -> Self {
         let on_open = self.on_open.clone();
+        let on_close = self.on_close.clone();
         self.child_builder = Some(Box::new(move |menu, builder| {
             let open = menu.borrow().is_some();
             t.toggle_state(open)
                 .when_some(builder, |el, builder| {
                     el.on_click(move |_event, window, cx| {
-                        show_menu(&builder, &menu, on_open.clone(), window, cx)
+                        show_menu(
+                            &builder,
+                            &menu,
+                            on_open.clone(),
+                            on_close.clone(),
+                            window,
+                            cx,
+                        )
                     })
                 })
                 .into_any_element()
@@ -201,12 +216,20 @@ impl<M: ManagedView> PopoverMenu<M> {
         tooltip_builder: impl Fn(&mut Window, &mut App) -> AnyView + 'static,
     ) -> Self {
         let on_open = self.on_open.clone();
+        let on_close = self.on_close.clone();
         self.child_builder = Some(Box::new(move |menu, builder| {
             let open = menu.borrow().is_some();
             t.toggle_state(open)
                 .when_some(builder, |el, builder| {
                     el.on_click(move |_, window, cx| {
-                        show_menu(&builder, &menu, on_open.clone(), window, cx)
+                        show_menu(
+                            &builder,
+                            &menu,
+                            on_open.clone(),
+                            on_close.clone(),
+                            window,
+                            cx,
+                        )
                     })
                     .when(!open, |t| {
                         t.tooltip(move |window, cx| tooltip_builder(window, cx))
@@ -236,12 +259,29 @@ impl<M: ManagedView> PopoverMenu<M> {
         self
     }
 
+    /// Opens the menu on the other side of the trigger, vertically, when it doesn't fit on the
+    /// side given by the anchor and there's more room on the other side.
+    pub fn auto_flip(mut self, auto_flip: bool) -> Self {
+        self.auto_flip = auto_flip;
+        self
+    }
+
     /// Attaches something upon opening the menu.
+    ///
+    /// Must be called before setting the trigger.
     pub fn on_open(mut self, on_open: Rc<dyn Fn(&mut Window, &mut App)>) -> Self {
         self.on_open = Some(on_open);
         self
     }
 
+    /// Attaches something upon the menu being dismissed.
+    ///
+    /// Must be called before setting the trigger.
+    pub fn on_close(mut self, on_close: Rc<dyn Fn(&mut Window, &mut App)>) -> Self {
+        self.on_close = Some(on_close);
+        self
+    }
+
     fn resolved_attach(&self) -> Anchor {
         self.attach
             .unwrap_or(self.attach.unwrap_or(match self.anchor {
@@ -256,6 +296,21 @@ impl<M: ManagedView> PopoverMenu<M> {
             }))
     }
 
+    fn should_flip(&self, element_state: &PopoverMenuElementState<M>, window: &Window) -> bool {
+        let (Some(child_bounds), Some(menu_height)) =
+            (element_state.child_bounds, element_state.menu_height)
+        else {
+            return false;
+        };
+        self.auto_flip
+            && menu_needs_flip(
+                self.anchor,
+                child_bounds,
+                menu_height,
+                window.viewport_size().height,
+            )
+    }
+
     fn resolved_offset(&self, window: &mut Window) -> Point<Pixels> {
         self.offset.unwrap_or_else(|| {
             // Default offset = 4px padding + 1px border
@@ -271,10 +326,31 @@ impl<M: ManagedView> PopoverMenu<M> {
     }
 }
 
+/// Whether a menu of the given height, anchored to the trigger with `anchor`, overflows the
+/// viewport on its own side while the other side of the trigger has more room.
+fn menu_needs_flip(
+    anchor: Anchor,
+    trigger_bounds: Bounds<Pixels>,
+    menu_height: Pixels,
+    viewport_height: Pixels,
+) -> bool {
+    let space_above = trigger_bounds.top();
+    let space_below = viewport_height - trigger_bounds.bottom();
+    let (space, other_space) = match anchor {
+        Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => (space_below, space_above),
+        Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => {
+            (space_above, space_below)
+        }
+        Anchor::LeftCenter | Anchor::RightCenter => return false,
+    };
+    menu_height > space && other_space > space
+}
+
 fn show_menu<M: ManagedView>(
     builder: &Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<M>>>,
     menu: &Rc<RefCell<Option<Entity<M>>>>,
     on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    on_close: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
     window: &mut Window,
     cx: &mut App,
 ) {
@@ -284,8 +360,13 @@ fn show_menu<M: ManagedView>(
     };
     let menu2 = menu.clone();
 
-    window
-        .subscribe(&new_menu, cx, move |modal, _: &DismissEvent, window, cx| {
+    // Menus can hand out the same entity on every open, so the subscription is dropped on
+    // dismissal rather than detached, or each earlier open would call `on_close` again.
+    let dismiss_subscription: Rc<RefCell<Option<Subscription>>> = Rc::default();
+    let subscription = window.subscribe(&new_menu, cx, {
+        let dismiss_subscription = dismiss_subscription.clone();
+        move |modal, _: &DismissEvent, window, cx| {
+            dismiss_subscription.borrow_mut().take();
             if modal.focus_handle(cx).contains_focused(window, cx)
                 && let Some(previous_focus_handle) = previous_focus_handle.as_ref()
             {
@@ -293,8 +374,12 @@ fn show_menu<M: ManagedView>(
             }
             *menu2.borrow_mut() = None;
             window.refresh();
-        })
-        .detach();
+            if let Some(on_close) = on_close.as_ref() {
+                on_close(window, cx);
+            }
+        }
+    });
+    *dismiss_subscription.borrow_mut() = Some(subscription);
 
     // Since menus are rendered in a deferred fashion, their focus handles are
     // not linked in the dispatch tree until after the deferred draw callback
@@ -319,6 +404,7 @@ fn show_menu<M: ManagedView>(
 pub struct PopoverMenuElementState<M> {
     menu: Rc<RefCell<Option<Entity<M>>>>,
     child_bounds: Option<Bounds<Pixels>>,
+    menu_height: Option<Pixels>,
 }
 
 impl<M> Clone for PopoverMenuElementState<M> {
@@ -326,6 +412,7 @@ impl<M> Clone for PopoverMenuElementState<M> {
         Self {
             menu: Rc::clone(&self.menu),
             child_bounds: self.child_bounds,
+            menu_height: self.menu_height,
         }
     }
 }
@@ -335,6 +422,7 @@ impl<M> Default for PopoverMenuElementState<M> {
         Self {
             menu: Rc::default(),
             child_bounds: None,
+            menu_height: None,
         }
     }
 }
@@ -342,8 +430,10 @@ impl<M> Default for PopoverMenuElementState<M> {
 pub struct PopoverMenuFrameState<M: ManagedView> {
     child_layout_id: Option<LayoutId>,
     child_element: Option<AnyElement>,
+    menu_layout_id: Option<LayoutId>,
     menu_element: Option<AnyElement>,
     menu_handle: Rc<RefCell<Option<Entity<M>>>>,
+    flipped: bool,
 }
 
 impl<M: ManagedView> Element for PopoverMenu<M> {
@@ -370,16 +460,24 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
             |element_state: Option<PopoverMenuElementState<M>>, window| {
                 let element_state = element_state.unwrap_or_default();
                 let mut menu_layout_id = None;
+                let flipped = self.should_flip(&element_state, window);
 
                 let menu_element = element_state.menu.borrow_mut().as_mut().map(|menu| {
                     let offset = self.resolved_offset(window);
+                    let (anchor, attach) = if flipped {
+                        (
+                            self.anchor.other_side_along(Axis::Vertical),
+                            self.resolved_attach().other_side_along(Axis::Vertical),
+                        )
+                    } else {
+                        (self.anchor, self.resolved_attach())
+                    };
                     let mut anchored = anchored()
                         .snap_to_window_with_margin(px(8.))
-                        .anchor(self.anchor)
+                        .anchor(anchor)
                         .offset(offset);
                     if let Some(child_bounds) = element_state.child_bounds {
-                        anchored =
-                            anchored.position(child_bounds.corner(self.resolved_attach()) + offset);
+                        anchored = anchored.position(child_bounds.corner(attach) + offset);
                     }
                     let mut element = deferred(anchored.child(div().occlude().child(menu.clone())))
                         .with_priority(1)
@@ -400,6 +498,7 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
                         menu_builder,
                         menu: element_state.menu.clone(),
                         on_open: self.on_open.clone(),
+                        on_close: self.on_close.clone(),
                     });
                 }
 
@@ -424,8 +523,10 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
                         PopoverMenuFrameState {
                             child_element,
                             child_layout_id,
+                            menu_layout_id,
                             menu_element,
                             menu_handle: element_state.menu.clone(),
+                            flipped,
                         },
                     ),
                     element_state,
@@ -451,16 +552,28 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
             menu.prepaint(window, cx);
         }
 
-        request_layout.child_layout_id.map(|layout_id| {
-            let bounds = window.layout_bounds(layout_id);
-            window.with_element_state(global_id.unwrap(), |element_state, _cx| {
+        let child_bounds = request_layout
+            .child_layout_id
+            .map(|layout_id| window.layout_bounds(layout_id));
+        // The menu is only measured once it has been laid out, so the first frame after opening
+        // can't know whether it fits; draw again if that frame placed it on the wrong side.
+        let menu_height = request_layout
+            .menu_layout_id
+            .map(|layout_id| window.layout_bounds(layout_id).size.height);
+        let needs_refresh =
+            window.with_element_state(global_id.unwrap(), |element_state, window| {
                 let mut element_state: PopoverMenuElementState<M> = element_state.unwrap();
-                element_state.child_bounds = Some(bounds);
-                ((), element_state)
+                element_state.child_bounds = child_bounds.or(element_state.child_bounds);
+                element_state.menu_height = menu_height;
+                let needs_refresh = menu_height.is_some()
+                    && self.should_flip(&element_state, window) != request_layout.flipped;
+                (needs_refresh, element_state)
             });
+        if needs_refresh {
+            window.refresh();
+        }
 
-            window.insert_hitbox(bounds, HitboxBehavior::Normal).id
-        })
+        child_bounds.map(|bounds| window.insert_hitbox(bounds, HitboxBehavior::Normal).id)
     }
 
     fn paint(
@@ -506,3 +619,230 @@ impl<M: ManagedView> IntoElement for PopoverMenu<M> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use gpui::{
+        ClickEvent, CursorStyle, EventEmitter, FocusHandle, Focusable, KeyUpEvent, Keystroke,
+        TestAppContext,
+    };
+
+    use crate::ContextMenu;
+
+    use super::*;
+
+    #[derive(IntoElement)]
+    struct TestTrigger {
+        focus_handle: FocusHandle,
+        on_click: Option<Box<dyn Fn(&ClickEvent, &mut Window, &mut App)>>,
+    }
+
+    impl Clickable for TestTrigger {
+        fn on_click(
+            mut self,
+            handler: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+        ) -> Self {
+            self.on_click = Some(Box::new(handler));
+            self
+        }
+
+        fn cursor_style(self, _: CursorStyle) -> Self {
+            self
+        }
+    }
+
+    impl Toggleable for TestTrigger {
+        fn toggle_state(self, _: bool) -> Self {
+            self
+        }
+    }
+
+    impl RenderOnce for TestTrigger {
+        fn render(self, _: &mut Window, _: &mut App) -> impl IntoElement {
+            div()
+                .id("trigger")
+                .size_4()
+                .track_focus(&self.focus_handle)
+                .when_some(self.on_click, |this, on_click| {
+                    this.on_click(move |event, window, cx| on_click(event, window, cx))
+                })
+        }
+    }
+
+    struct TestMenu {
+        focus_handle: FocusHandle,
+    }
+
+    impl EventEmitter<DismissEvent> for TestMenu {}
+
+    impl Focusable for TestMenu {
+        fn focus_handle(&self, _: &App) -> FocusHandle {
+            self.focus_handle.clone()
+        }
+    }
+
+    impl Render for TestMenu {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_4().track_focus(&self.focus_handle)
+        }
+    }
+
+    struct TestView {
+        trigger_focus_handle: FocusHandle,
+        menu_handle: PopoverMenuHandle<TestMenu>,
+    }
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                PopoverMenu::new("popover")
+                    .menu(|_, cx| {
+                        Some(cx.new(|cx| TestMenu {
+                            focus_handle: cx.focus_handle(),
+                        }))
+                    })
+                    .trigger(TestTrigger {
+                        focus_handle: self.trigger_focus_handle.clone(),
+                        on_click: None,
+                    })
+                    .with_handle(self.menu_handle.clone()),
+            )
+        }
+    }
+
+    #[test]
+    fn menu_flips_only_when_it_does_not_fit() {
+        let viewport_height = px(400.);
+        let trigger_near_bottom = Bounds::new(point(px(0.), px(300.)), size(px(20.), px(20.)));
+
+        // 80px remain below the trigger and 300px above it.
+        assert!(!menu_needs_flip(
+            Anchor::TopLeft,
+            trigger_near_bottom,
+            px(60.),
+            viewport_height
+        ));
+        assert!(menu_needs_flip(
+            Anchor::TopLeft,
+            trigger_near_bottom,
+            px(120.),
+            viewport_height
+        ));
+        assert!(!menu_needs_flip(
+            Anchor::BottomLeft,
+            trigger_near_bottom,
+            px(120.),
+            viewport_height
+        ));
+
+        // A menu that fits on neither side stays where the anchor puts it unless the other
+        // side has more room.
+        let trigger_near_top = Bounds::new(point(px(0.), px(80.)), size(px(20.), px(20.)));
+        assert!(!menu_needs_flip(
+            Anchor::TopLeft,
+            trigger_near_top,
+            px(500.),
+            viewport_height
+        ));
+        assert!(menu_needs_flip(
+            Anchor::BottomLeft,
+            trigger_near_top,
+            px(500.),
+            viewport_height
+        ));
+        assert!(!menu_needs_flip(
+            Anchor::RightCenter,
+            trigger_near_bottom,
+            px(500.),
+            viewport_height
+        ));
+    }
+
+    #[gpui::test]
+    fn enter_opens_menu_from_focused_trigger(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, cx| TestView {
+            trigger_focus_handle: cx.focus_handle(),
+            menu_handle: PopoverMenuHandle::default(),
+        });
+        let (trigger_focus_handle, menu_handle) = view.read_with(cx, |view, _| {
+            (view.trigger_focus_handle.clone(), view.menu_handle.clone())
+        });
+
+        cx.update(|window, cx| window.focus(&trigger_focus_handle, cx));
+        cx.run_until_parked();
+        assert!(!menu_handle.is_deployed());
+
+        cx.simulate_keystrokes("enter");
+        cx.simulate_event(KeyUpEvent {
+            keystroke: Keystroke::parse("enter").expect("valid keystroke"),
+        });
+        assert!(menu_handle.is_deployed());
+    }
+
+    #[gpui::test]
+    fn on_open_and_on_close_fire_once_per_open(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let builder: Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<ContextMenu>>> =
+            Rc::new(|window, cx| {
+                Some(ContextMenu::build(window, cx, |menu, _, _| {
+                    menu.entry("Entry", None, |_, _| {})
+                }))
+            });
+        assert_open_and_close_counts(&builder, cx);
+    }
+
+    #[gpui::test]
+    fn on_close_fires_once_per_open_when_the_menu_is_reused(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let reused_menu = cx.update(|window, cx| {
+            ContextMenu::build(window, cx, |menu, _, _| {
+                menu.entry("Entry", None, |_, _| {})
+            })
+        });
+        let builder: Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<ContextMenu>>> =
+            Rc::new(move |_, _| Some(reused_menu.clone()));
+        assert_open_and_close_counts(&builder, cx);
+    }
+
+    fn assert_open_and_close_counts(
+        builder: &Rc<dyn Fn(&mut Window, &mut App) -> Option<Entity<ContextMenu>>>,
+        cx: &mut gpui::VisualTestContext,
+    ) {
+        let open_count = Rc::new(Cell::new(0));
+        let close_count = Rc::new(Cell::new(0));
+        let on_open: Rc<dyn Fn(&mut Window, &mut App)> = Rc::new({
+            let open_count = open_count.clone();
+            move |_, _| open_count.set(open_count.get() + 1)
+        });
+        let on_close: Rc<dyn Fn(&mut Window, &mut App)> = Rc::new({
+            let close_count = close_count.clone();
+            move |_, _| close_count.set(close_count.get() + 1)
+        });
+        let menu = Rc::new(RefCell::new(None));
+
+        for expected_count in 1..=3 {
+            cx.update(|window, cx| {
+                show_menu(
+                    builder,
+                    &menu,
+                    Some(on_open.clone()),
+                    Some(on_close.clone()),
+                    window,
+                    cx,
+                )
+            });
+            assert_eq!(open_count.get(), expected_count);
+            assert_eq!(close_count.get(), expected_count - 1);
+
+            let opened_menu = menu.borrow().clone().expect("menu should be open");
+            cx.update(|_, cx| opened_menu.update(cx, |_, cx| cx.emit(DismissEvent)));
+            cx.run_until_parked();
+
+            assert!(menu.borrow().is_none());
+            assert_eq!(open_count.get(), expected_count);
+            assert_eq!(close_count.get(), expected_count);
+        }
+    }
+}
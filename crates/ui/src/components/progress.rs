@@ -1,5 +1,7 @@
 mod circular_progress;
 mod progress_bar;
+mod progress_fill;
 
 pub use circular_progress::*;
 pub use progress_bar::*;
+pub use progress_fill::*;
@@ -1,8 +1,54 @@
 use documented::Documented;
-use gpui::{Hsla, PathBuilder, canvas, point};
-use std::f32::consts::PI;
+use gpui::{
+    Animation, AnimationExt, AnyElement, Bounds, FontWeight, Hsla, Path, PathBuilder, Point, Rgba,
+    canvas, point,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    f32::consts::PI,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+use theme::{StatusColors, ThemeColors};
 
+use crate::components::ProgressFill;
 use crate::prelude::*;
+use crate::utils::{apca_contrast, ensure_minimum_contrast};
+
+/// The direction in which the progress arc grows from its start angle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressDirection {
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Where a [`CircularProgress`] stroke sits relative to the ring's nominal circle, which is
+/// inset from the element's bounds by the stroke width.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeAlign {
+    /// The stroke straddles the nominal circle, leaving half a stroke of space around it.
+    #[default]
+    Center,
+    /// The stroke lies inside the nominal circle, leaving a full stroke of space around it.
+    Inside,
+    /// The stroke lies outside the nominal circle, so its outer edge is flush with the
+    /// element's bounds.
+    Outside,
+}
+
+/// Whether the work a [`CircularProgress`] tracks is still running or has finished.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressState {
+    /// The ring shows the current value.
+    #[default]
+    Active,
+    /// The ring is drawn full in the theme's success color, with a check in the center.
+    Success,
+    /// The ring is drawn full in the theme's error color, with a cross in the center.
+    Failed,
+}
 
 /// A circular progress indicator that displays progress as an arc growing clockwise from the top.
 #[derive(IntoElement, RegisterComponent, Documented)]
@@ -10,23 +56,86 @@ pub struct CircularProgress {
     value: f32,
     max_value: f32,
     size: Pixels,
-    stroke_width: Pixels,
+    start_angle: f32,
+    direction: ProgressDirection,
+    countdown: bool,
+    grow_from_center: bool,
+    start_marker: bool,
+    stroke_width: Option<Pixels>,
+    thickness_ratio: Option<f32>,
+    min_stroke_width: Pixels,
+    stroke_align: StrokeAlign,
     bg_color: Hsla,
-    progress_color: Hsla,
+    fill: ProgressFill,
+    center: Option<AnyElement>,
+    state: ProgressState,
+    rotation_id: Option<ElementId>,
+    over_badge: bool,
+    over_color: Option<Hsla>,
+    show_after: Option<(ElementId, Duration)>,
 }
 
 impl CircularProgress {
+    /// Creates a ring colored from the theme. When the theme's track and fill colors are
+    /// hard to tell apart, as in some high-contrast themes, the fill is adjusted to stand
+    /// out from the track and thin strokes are thickened.
     pub fn new(value: f32, max_value: f32, size: Pixels, cx: &App) -> Self {
+        let (bg_color, fill_color, min_stroke_width) =
+            default_colors(cx.theme().colors(), cx.theme().status());
         Self {
             value,
             max_value,
             size,
-            stroke_width: px(4.0),
-            bg_color: cx.theme().colors().border_variant,
-            progress_color: cx.theme().status().info,
+            start_angle: DEFAULT_START_ANGLE,
+            direction: ProgressDirection::default(),
+            countdown: false,
+            grow_from_center: false,
+            start_marker: false,
+            stroke_width: None,
+            thickness_ratio: None,
+            min_stroke_width,
+            stroke_align: StrokeAlign::default(),
+            bg_color,
+            fill: ProgressFill::Solid(fill_color),
+            center: None,
+            state: ProgressState::default(),
+            rotation_id: None,
+            over_badge: false,
+            over_color: None,
+            show_after: None,
         }
     }
 
+    /// Creates a ring from a fraction of the work done, where `1.0` is complete.
+    pub fn from_fraction(fraction: f32, size: Pixels, cx: &App) -> Self {
+        Self::new(fraction, 1.0, size, cx)
+    }
+
+    /// Creates a ring from a whole percentage, clamped to at most `100`.
+    pub fn percent(percent: u8, size: Pixels, cx: &App) -> Self {
+        Self::new(f32::from(percent.min(100)), 100.0, size, cx)
+    }
+
+    /// Lays out one default ring per `(id, value, max_value)` item in a wrapping row, for
+    /// screens that show many small rings at once. Each ring sits in an element with its id.
+    ///
+    /// Rings of the same size share one cached track, so only their progress arcs are built
+    /// per ring.
+    pub fn render_grid(
+        items: &[(ElementId, f32, f32)],
+        size: Pixels,
+        cx: &App,
+    ) -> impl IntoElement + use<> {
+        h_flex()
+            .flex_wrap()
+            .gap_1()
+            .children(items.iter().map(|(id, value, max_value)| {
+                div()
+                    .id(id.clone())
+                    .child(Self::new(*value, *max_value, size, cx))
+            }))
+    }
+
     /// Sets the current progress value.
     pub fn value(mut self, value: f32) -> Self {
         self.value = value;
@@ -45,9 +154,65 @@ impl CircularProgress {
         self
     }
 
+    /// Sets the angle, in degrees, at which the progress arc starts.
+    ///
+    /// Angles are measured clockwise from 3 o'clock, so the default of `-90` is the top.
+    pub fn start_angle(mut self, degrees: f32) -> Self {
+        self.start_angle = degrees;
+        self
+    }
+
+    /// Sets the direction in which the progress arc grows.
+    pub fn direction(mut self, direction: ProgressDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Starts the ring full and drains it as the value grows, as for a timeout where the value
+    /// is the time elapsed. The ring is empty once the value reaches the maximum.
+    ///
+    /// The remaining arc still runs from the start angle in the ring's direction, so it
+    /// shrinks back towards the start angle.
+    pub fn countdown(mut self, countdown: bool) -> Self {
+        self.countdown = countdown;
+        self
+    }
+
+    /// Centers the arc on the start angle, so it grows out from there equally in both
+    /// directions instead of sweeping away from it.
+    pub fn grow_from_center(mut self, grow_from_center: bool) -> Self {
+        self.grow_from_center = grow_from_center;
+        self
+    }
+
+    /// Paints a dot in the progress color where the arc starts, on top of the track, so an
+    /// empty ring still shows where progress will begin.
+    pub fn start_marker(mut self, start_marker: bool) -> Self {
+        self.start_marker = start_marker;
+        self
+    }
+
     /// Sets the stroke width of the circular progress indicator.
+    ///
+    /// Strokes wider than [`CircularProgress::max_stroke_width`] allows for the ring's size
+    /// are clamped when rendering.
     pub fn stroke_width(mut self, stroke_width: Pixels) -> Self {
-        self.stroke_width = stroke_width;
+        self.stroke_width = Some(stroke_width);
+        self
+    }
+
+    /// Sizes the stroke as a fraction of the ring's radius, between `0.0` and `1.0`, so it
+    /// stays in proportion as the ring's size changes. An explicit
+    /// [`stroke_width`](Self::stroke_width) takes precedence.
+    pub fn thickness_ratio(mut self, ratio: f32) -> Self {
+        self.thickness_ratio = Some(ratio.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Sets where the stroke sits relative to the ring's nominal circle. Use
+    /// [`StrokeAlign::Outside`] to keep the ring flush with the element's bounds.
+    pub fn stroke_align(mut self, stroke_align: StrokeAlign) -> Self {
+        self.stroke_align = stroke_align;
         self
     }
 
@@ -59,114 +224,853 @@ impl CircularProgress {
 
     /// Sets the progress arc color.
     pub fn progress_color(mut self, color: Hsla) -> Self {
-        self.progress_color = color;
+        self.fill = ProgressFill::Solid(color);
+        self
+    }
+
+    /// Sets how the progress arc is colored, such as with a gradient that follows the ring.
+    pub fn fill(mut self, fill: impl Into<ProgressFill>) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
+    /// Places an element, such as an icon, in the empty center of the ring.
+    ///
+    /// The element is centered over the ring without affecting its size. Content larger
+    /// than the ring is not clipped and overflows it equally on every side.
+    pub fn center(mut self, element: impl IntoElement) -> Self {
+        self.center = Some(element.into_any_element());
         self
     }
+
+    /// Marks the tracked work as finished, which overrides the value and fill.
+    ///
+    /// Terminal states draw a full ring whatever the value, and their glyph is only shown when
+    /// no [`center`](Self::center) element was given.
+    pub fn state(mut self, state: ProgressState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Slowly turns the whole ring while it keeps showing the current value, to suggest that
+    /// work is ongoing, as in "Syncing 40%".
+    ///
+    /// The ring turns once every few seconds in the direction progress grows. Rotation is
+    /// ignored once the [`state`](Self::state) is [`ProgressState::Success`] or
+    /// [`ProgressState::Failed`].
+    ///
+    /// NOTE: This method uses the location of the caller to identify the animation, so rings
+    ///       rendered from the same call site, such as in a list, turn in step.
+    #[track_caller]
+    pub fn rotating(mut self, rotating: bool) -> Self {
+        self.rotation_id =
+            rotating.then(|| ElementId::CodeLocation(*std::panic::Location::caller()));
+        self
+    }
+
+    /// Keeps the ring hidden until it has been rendered for `delay`, then fades it in, so work
+    /// that usually finishes quickly doesn't flash a ring on screen.
+    ///
+    /// NOTE: The first frame the ring was rendered in is kept in element state under `id`, so
+    ///       the id must stay the same across frames and be unique among the rings shown at
+    ///       once. A ring that stops being rendered before the delay elapses never appears,
+    ///       and starts waiting again if it's rendered later. The hidden ring still takes up
+    ///       its size in the layout.
+    pub fn show_after(mut self, id: impl Into<ElementId>, delay: Duration) -> Self {
+        self.show_after = Some((id.into(), delay));
+        self
+    }
+
+    /// Shows how far the value is over the maximum, as in `+50%`, in a badge at the top-right
+    /// corner of the ring.
+    ///
+    /// The ring itself stops at a full turn, so this is how an exceeded quota shows its
+    /// overage. The badge is hidden while the value is at or below the maximum, and in the
+    /// [`ProgressState::Success`] and [`ProgressState::Failed`] states.
+    pub fn over_badge(mut self, over_badge: bool) -> Self {
+        self.over_badge = over_badge;
+        self
+    }
+
+    /// Draws how far the value is over the maximum as a second arc in `color`, on top of the
+    /// full ring, which keeps its own fill. The second arc grows from the start angle like the
+    /// first and covers a whole turn at twice the maximum.
+    ///
+    /// The layers are painted from the bottom up: the track, the
+    /// [start marker](Self::start_marker), the progress arc and then the overflow arc. The
+    /// overflow arc is only drawn in the [`ProgressState::Active`] state.
+    pub fn over_color(mut self, color: Hsla) -> Self {
+        self.over_color = Some(color);
+        self
+    }
+
+    /// Returns the widest stroke a ring of the given diameter can draw. At this width the
+    /// inside edge of the stroke reaches the center of the ring.
+    pub fn max_stroke_width(diameter: Pixels) -> Pixels {
+        diameter / 3.0
+    }
+
+    /// Returns the diameter of the ring, which is also the width and height it takes up in
+    /// layout. Strokes stay within it whatever their [alignment](Self::stroke_align).
+    pub fn diameter(&self) -> Pixels {
+        self.size
+    }
+
+    /// Returns the value as a fraction of the maximum, which can fall outside 0 to 1 when the
+    /// value does. See [`normalized_progress`](Self::normalized_progress) for what's drawn.
+    pub fn fraction(&self) -> f32 {
+        self.value / self.max_value
+    }
+
+    /// Returns the fraction of the ring covered by the progress arc, between 0 and 1.
+    pub fn normalized_progress(&self) -> f32 {
+        normalized_progress(self.drawn_value(), self.max_value)
+    }
+
+    /// The value the arc is drawn for, which counts down from the maximum in
+    /// [countdown](Self::countdown) mode.
+    fn drawn_value(&self) -> f32 {
+        if self.countdown {
+            self.max_value - self.value
+        } else {
+            self.value
+        }
+    }
+
+    /// Returns where the progress arc currently ends, relative to the top-left corner of a
+    /// ring with the given diameter.
+    ///
+    /// With no progress this is the start of the arc, and with full progress the arc has
+    /// wrapped back around to it.
+    pub fn progress_end_point(&self, diameter: Pixels) -> Point<Pixels> {
+        let progress = self.normalized_progress();
+        let start_angle = arc_start_angle(
+            self.start_angle,
+            self.direction,
+            progress,
+            self.grow_from_center,
+        );
+        self.point_at_angle(diameter, end_angle(start_angle, self.direction, progress))
+    }
+
+    /// Returns the point at the start angle, relative to the top-left corner of a ring with
+    /// the given diameter. This is where the arc starts, or its middle when it
+    /// [grows from the center](Self::grow_from_center), and where the
+    /// [start marker](Self::start_marker) is painted.
+    pub fn start_point(&self, diameter: Pixels) -> Point<Pixels> {
+        self.point_at_angle(diameter, self.start_angle)
+    }
+
+    fn point_at_angle(&self, diameter: Pixels, degrees: f32) -> Point<Pixels> {
+        let center = point(diameter / 2.0, diameter / 2.0);
+        angle_to_point(
+            center,
+            arc_radius(
+                diameter,
+                clamp_stroke_width(diameter, self.resolved_stroke_width(diameter)),
+                self.stroke_align,
+            ),
+            degrees,
+        )
+    }
+
+    /// The stroke width for a ring of the given diameter, before clamping: the explicit width,
+    /// else the [thickness ratio](Self::thickness_ratio) of the radius, else the default,
+    /// raised to the theme's minimum.
+    fn resolved_stroke_width(&self, diameter: Pixels) -> Pixels {
+        let stroke_width = match (self.stroke_width, self.thickness_ratio) {
+            (Some(stroke_width), _) => stroke_width,
+            (None, Some(ratio)) => diameter / 2.0 * ratio,
+            (None, None) => DEFAULT_STROKE_WIDTH,
+        };
+        stroke_width.max(self.min_stroke_width)
+    }
+
+    /// Draws the ring as a standalone SVG document with the given diameter, using the same
+    /// geometry as on screen, for snapshot tests and exporting.
+    ///
+    /// Only the track and the progress arc are drawn, from the value and fill as configured;
+    /// the [state](Self::state), center content and badge are left out.
+    pub fn to_svg(&self, diameter: Pixels) -> String {
+        let stroke_width = clamp_stroke_width(diameter, self.resolved_stroke_width(diameter));
+        let radius = arc_radius(diameter, stroke_width, self.stroke_align);
+        let center = point(diameter / 2.0, diameter / 2.0);
+        let start_angle = normalize_angle(arc_start_angle(
+            self.start_angle,
+            self.direction,
+            self.normalized_progress(),
+            self.grow_from_center,
+        ));
+        let stroke = |color: Hsla| {
+            format!(
+                r#"fill="none" stroke="{}" stroke-width="{}""#,
+                svg_color(color),
+                svg_number(stroke_width)
+            )
+        };
+
+        let mut arcs = Vec::new();
+        if self.fill.is_solid() {
+            let data = match progress_arc(
+                self.drawn_value(),
+                self.max_value,
+                start_angle,
+                self.direction,
+                center,
+                radius,
+            ) {
+                ProgressArc::None => None,
+                ProgressArc::Full => {
+                    let right = point(center.x + radius, center.y);
+                    let left = point(center.x - radius, center.y);
+                    Some(format!(
+                        "{} {} Z",
+                        svg_arc(right, left, radius, false, true),
+                        svg_arc_to(right, radius, false, true),
+                    ))
+                }
+                ProgressArc::Partial {
+                    start,
+                    end,
+                    large_arc,
+                    sweep,
+                } => Some(svg_arc(start, end, radius, large_arc, sweep)),
+            };
+            arcs.extend(data.map(|data| (data, self.fill.color_at(0.0))));
+        } else {
+            let sweep = self.direction == ProgressDirection::Clockwise;
+            for segment in fill_segments(start_angle, self.direction, self.normalized_progress()) {
+                arcs.push((
+                    svg_arc(
+                        angle_to_point(center, radius, segment.start_angle),
+                        angle_to_point(center, radius, segment.end_angle),
+                        radius,
+                        false,
+                        sweep,
+                    ),
+                    self.fill.color_at(segment.position),
+                ));
+            }
+        }
+
+        let size = svg_number(diameter);
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#
+        );
+        svg.push_str(&format!(
+            r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+            svg_number(center.x),
+            svg_number(center.y),
+            svg_number(radius),
+            stroke(self.bg_color)
+        ));
+        for (data, color) in arcs {
+            svg.push_str(&format!(r#"<path d="{data}" {}/>"#, stroke(color)));
+        }
+        svg.push_str("</svg>");
+        svg
+    }
 }
 
-impl RenderOnce for CircularProgress {
-    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
-        let value = self.value;
-        let max_value = self.max_value;
-        let size = self.size;
-        let bg_color = self.bg_color;
-        let progress_color = self.progress_color;
+/// An SVG path that moves to `start` and draws an arc to `end`.
+fn svg_arc(
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    radius: Pixels,
+    large_arc: bool,
+    sweep: bool,
+) -> String {
+    format!(
+        "M {} {} {}",
+        svg_number(start.x),
+        svg_number(start.y),
+        svg_arc_to(end, radius, large_arc, sweep)
+    )
+}
+
+/// The SVG command for an arc from the current point to `end`.
+fn svg_arc_to(end: Point<Pixels>, radius: Pixels, large_arc: bool, sweep: bool) -> String {
+    format!(
+        "A {radius} {radius} 0 {} {} {} {}",
+        u8::from(large_arc),
+        u8::from(sweep),
+        svg_number(end.x),
+        svg_number(end.y),
+        radius = svg_number(radius),
+    )
+}
+
+/// Formats a length for SVG, rounded to thousandths so float noise doesn't reach snapshots.
+fn svg_number(value: Pixels) -> String {
+    // Adding zero turns a rounded `-0` into `0`.
+    ((value.as_f32() * 1000.0).round() / 1000.0 + 0.0).to_string()
+}
+
+fn svg_color(color: Hsla) -> String {
+    format!("#{:08x}", u32::from(Rgba::from(color)))
+}
+
+/// The top of the ring, in degrees clockwise from 3 o'clock.
+const DEFAULT_START_ANGLE: f32 = -90.0;
+
+const DEFAULT_STROKE_WIDTH: Pixels = px(4.0);
+
+/// The least APCA contrast the theme's default fill needs against its track before the ring
+/// adjusts its colors. The default themes clear it comfortably.
+const MINIMUM_FILL_CONTRAST: f32 = 30.0;
+
+/// The APCA contrast an adjusted fill is given against its track.
+const ADJUSTED_FILL_CONTRAST: f32 = 60.0;
+
+/// The thinnest stroke drawn once the fill has been adjusted, so the arc stays easy to see.
+const ADJUSTED_MIN_STROKE_WIDTH: Pixels = px(3.0);
+
+/// Returns the default track color, fill color and minimum stroke width for a theme.
+///
+/// Themes don't say whether they are high contrast, so this looks at the colors themselves:
+/// when the fill is hard to tell apart from the track, it is pushed away from the track
+/// and the stroke is kept from getting too thin.
+fn default_colors(colors: &ThemeColors, status: &StatusColors) -> (Hsla, Hsla, Pixels) {
+    let track = colors.border_variant;
+    let fill = status.info;
+    if apca_contrast(fill, track).abs() >= MINIMUM_FILL_CONTRAST {
+        return (track, fill, px(0.));
+    }
+    (
+        track,
+        ensure_minimum_contrast(fill, track, ADJUSTED_FILL_CONTRAST),
+        ADJUSTED_MIN_STROKE_WIDTH,
+    )
+}
 
+/// Progress at or above this fraction is drawn as a full ring, since an arc ending
+/// where it starts would be degenerate.
+const FULL_RING_THRESHOLD: f32 = 0.999;
+
+/// The part of the ring covered by the progress arc.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProgressArc {
+    None,
+    Full,
+    /// Sweeps from `start` to `end`, clockwise on screen when `sweep` is set.
+    Partial {
+        start: Point<Pixels>,
+        end: Point<Pixels>,
+        large_arc: bool,
+        sweep: bool,
+    },
+}
+
+fn normalized_progress(value: f32, max_value: f32) -> f32 {
+    (value / max_value).clamp(0.0, 1.0)
+}
+
+/// The fraction of a turn by which the value is over the maximum, capped at a whole turn, or
+/// `None` while it's within the maximum.
+fn overflow_fraction(value: f32, max_value: f32) -> Option<f32> {
+    let overage = value - max_value;
+    (overage > 0.0).then(|| normalized_progress(overage, max_value))
+}
+
+/// The label for the over-limit badge, such as `+50%`, or `None` while the value is within
+/// the maximum. Any overage shows as at least `+1%`, so the badge never reads `+0%`.
+fn over_badge_label(value: f32, max_value: f32) -> Option<SharedString> {
+    let overage = value / max_value - 1.0;
+    if !overage.is_finite() || overage <= 0.0 {
+        return None;
+    }
+    Some(format!("+{}%", (overage * 100.0).round().max(1.0)).into())
+}
+
+fn clamp_stroke_width(diameter: Pixels, stroke_width: Pixels) -> Pixels {
+    stroke_width.clamp(px(0.), CircularProgress::max_stroke_width(diameter))
+}
+
+/// Logs the first stroke that [`clamp_stroke_width`] will narrow. Rings render every frame
+/// while animating, so later ones are left out rather than flooding the log.
+fn warn_if_stroke_too_wide(diameter: Pixels, stroke_width: Pixels) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+
+    let clamped = clamp_stroke_width(diameter, stroke_width);
+    if clamped < stroke_width && !WARNED.swap(true, Ordering::Relaxed) {
+        log::warn!(
+            "circular progress stroke width {stroke_width:?} is too wide for a {diameter:?} ring, using {clamped:?}"
+        );
+    }
+}
+
+/// The radius the stroke is centered on. The stroke is inset from the element's bounds, so
+/// with a stroke clamped by [`clamp_stroke_width`] this stays positive. An inside stroke too
+/// wide for the ring is kept from crossing its center.
+fn arc_radius(diameter: Pixels, stroke_width: Pixels, stroke_align: StrokeAlign) -> Pixels {
+    let nominal_radius = (diameter / 2.0) - stroke_width;
+    match stroke_align {
+        StrokeAlign::Center => nominal_radius,
+        StrokeAlign::Inside => (nominal_radius - stroke_width / 2.0).max(stroke_width / 2.0),
+        StrokeAlign::Outside => nominal_radius + stroke_width / 2.0,
+    }
+}
+
+/// Wraps an angle in degrees into `[0, 360)`.
+fn normalize_angle(degrees: f32) -> f32 {
+    let normalized = degrees.rem_euclid(360.0);
+    // Tiny negative angles round up to exactly 360 in `rem_euclid`.
+    if normalized >= 360.0 { 0.0 } else { normalized }
+}
+
+/// Returns the angle, normalized into `[0, 360)`, at which an arc covering `progress` of the
+/// ring ends.
+fn end_angle(start_angle: f32, direction: ProgressDirection, progress: f32) -> f32 {
+    let span = progress * 360.0;
+    normalize_angle(match direction {
+        ProgressDirection::Clockwise => start_angle + span,
+        ProgressDirection::CounterClockwise => start_angle - span,
+    })
+}
+
+/// Where an arc covering `progress` of the ring starts. When it grows from the center, it
+/// starts half of its span before `start_angle` so that it's centered on it.
+fn arc_start_angle(
+    start_angle: f32,
+    direction: ProgressDirection,
+    progress: f32,
+    grow_from_center: bool,
+) -> f32 {
+    if grow_from_center {
+        end_angle(start_angle, direction, -progress / 2.0)
+    } else {
+        start_angle
+    }
+}
+
+fn angle_to_point(center: Point<Pixels>, radius: Pixels, degrees: f32) -> Point<Pixels> {
+    let radians = degrees * PI / 180.0;
+    point(
+        center.x + radius * radians.cos(),
+        center.y + radius * radians.sin(),
+    )
+}
+
+fn progress_arc(
+    value: f32,
+    max_value: f32,
+    start_angle: f32,
+    direction: ProgressDirection,
+    center: Point<Pixels>,
+    radius: Pixels,
+) -> ProgressArc {
+    let progress = normalized_progress(value, max_value);
+    if progress >= FULL_RING_THRESHOLD {
+        ProgressArc::Full
+    } else if progress > 0.0 {
+        ProgressArc::Partial {
+            start: angle_to_point(center, radius, start_angle),
+            end: angle_to_point(center, radius, end_angle(start_angle, direction, progress)),
+            // The flag depends only on how much of the ring is covered, never on where
+            // the arc starts or ends: deriving it from the end angle picks the wrong arc
+            // once a counter-clockwise sweep wraps past 0°.
+            large_arc: progress > 0.5,
+            sweep: direction == ProgressDirection::Clockwise,
+        }
+    } else {
+        ProgressArc::None
+    }
+}
+
+/// Gradient fills are drawn as short arcs of one color each, since paths can only be
+/// filled with linear gradients.
+const FILL_SEGMENT_DEGREES: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FillSegment {
+    start_angle: f32,
+    end_angle: f32,
+    /// Where the middle of the segment falls along the ring, as a fraction of a full turn.
+    position: f32,
+}
+
+fn fill_segments(
+    start_angle: f32,
+    direction: ProgressDirection,
+    progress: f32,
+) -> Vec<FillSegment> {
+    let progress = if progress >= FULL_RING_THRESHOLD {
+        1.0
+    } else {
+        progress.max(0.0)
+    };
+    let count = (progress * 360.0 / FILL_SEGMENT_DEGREES).ceil() as usize;
+    (0..count)
+        .map(|ix| {
+            let segment_start = ix as f32 / count as f32 * progress;
+            let segment_end = (ix + 1) as f32 / count as f32 * progress;
+            FillSegment {
+                start_angle: end_angle(start_angle, direction, segment_start),
+                end_angle: end_angle(start_angle, direction, segment_end),
+                position: (segment_start + segment_end) / 2.0,
+            }
+        })
+        .collect()
+}
+
+/// How long a [rotating](CircularProgress::rotating) ring takes to turn once.
+const ROTATION_PERIOD: Duration = Duration::from_secs(3);
+
+/// How long a ring delayed with [`show_after`](CircularProgress::show_after) takes to fade in.
+const FADE_IN_DURATION: Duration = Duration::from_millis(150);
+
+/// Whether a ring first rendered at `first_shown` has waited out its `delay` by `now`.
+fn delay_elapsed(first_shown: Instant, now: Instant, delay: Duration) -> bool {
+    now.saturating_duration_since(first_shown) >= delay
+}
+
+/// Everything needed to paint the ring once the state has been resolved, kept separate so a
+/// rotating ring can be repainted at a new start angle on every frame.
+#[derive(Clone)]
+struct RingPaint {
+    value: f32,
+    max_value: f32,
+    size: Pixels,
+    start_angle: f32,
+    direction: ProgressDirection,
+    grow_from_center: bool,
+    start_marker: bool,
+    stroke_width: Pixels,
+    stroke_align: StrokeAlign,
+    bg_color: Hsla,
+    fill: ProgressFill,
+    /// The fraction of a turn the value is over the maximum, drawn on top in the given color.
+    overflow: Option<(f32, Hsla)>,
+}
+
+impl RingPaint {
+    /// Moves the start of the ring by `turns` full turns in the direction progress grows.
+    fn rotated(mut self, turns: f32) -> Self {
+        self.start_angle = end_angle(self.start_angle, self.direction, turns);
+        self
+    }
+
+    fn into_canvas(self) -> impl IntoElement {
         canvas(
             |_, _, _| {},
-            move |bounds, _, window, _cx| {
-                let current_value = value;
+            move |bounds, _, window, _cx| self.paint(bounds, window),
+        )
+        .size_full()
+    }
 
-                let center_x = bounds.origin.x + bounds.size.width / 2.0;
-                let center_y = bounds.origin.y + bounds.size.height / 2.0;
+    fn paint(&self, bounds: Bounds<Pixels>, window: &mut Window) {
+        let center_x = bounds.origin.x + bounds.size.width / 2.0;
+        let center_y = bounds.origin.y + bounds.size.height / 2.0;
 
-                let stroke_width = self.stroke_width;
-                let radius = (size / 2.0) - stroke_width;
+        let stroke_width = clamp_stroke_width(self.size, self.stroke_width);
+        let radius = arc_radius(self.size, stroke_width, self.stroke_align);
 
-                // Draw background circle (full 360 degrees)
-                let mut bg_builder = PathBuilder::stroke(stroke_width);
+        let center = point(center_x, center_y);
+        if let Some(path) = background_ring_path(center, radius, stroke_width) {
+            window.paint_path(path, self.bg_color);
+        }
 
-                // Start at rightmost point
-                bg_builder.move_to(point(center_x + radius, center_y));
+        if self.start_marker
+            && let Some(path) = build_dot(
+                angle_to_point(center, radius, self.start_angle),
+                stroke_width / 2.0,
+            )
+        {
+            window.paint_path(path, self.fill.color_at(0.0));
+        }
 
-                // Draw full circle using two 180-degree arcs
-                bg_builder.arc_to(
+        let progress = normalized_progress(self.value, self.max_value);
+        let start_angle = arc_start_angle(
+            self.start_angle,
+            self.direction,
+            progress,
+            self.grow_from_center,
+        );
+        if !self.fill.is_solid() {
+            let sweep = self.direction == ProgressDirection::Clockwise;
+            for segment in fill_segments(start_angle, self.direction, progress) {
+                let mut segment_builder = PathBuilder::stroke(stroke_width);
+                segment_builder.move_to(angle_to_point(center, radius, segment.start_angle));
+                segment_builder.arc_to(
                     point(radius, radius),
                     px(0.),
                     false,
-                    true,
-                    point(center_x - radius, center_y),
+                    sweep,
+                    angle_to_point(center, radius, segment.end_angle),
                 );
-                bg_builder.arc_to(
-                    point(radius, radius),
-                    px(0.),
-                    false,
-                    true,
-                    point(center_x + radius, center_y),
-                );
-                bg_builder.close();
-
-                if let Ok(path) = bg_builder.build() {
-                    window.paint_path(path, bg_color);
+                if let Ok(path) = segment_builder.build() {
+                    window.paint_path(path, self.fill.color_at(segment.position));
                 }
+            }
+        } else if let Some(path) = arc_path(
+            progress_arc(
+                self.value,
+                self.max_value,
+                start_angle,
+                self.direction,
+                center,
+                radius,
+            ),
+            center,
+            radius,
+            stroke_width,
+        ) {
+            window.paint_path(path, self.fill.color_at(0.0));
+        }
 
-                // Draw progress arc if there's any progress
-                let progress = (current_value / max_value).clamp(0.0, 1.0);
-                if progress > 0.0 {
-                    let mut progress_builder = PathBuilder::stroke(stroke_width);
-
-                    // Handle 100% progress as a special case by drawing a full circle
-                    if progress >= 0.999 {
-                        // Start at rightmost point
-                        progress_builder.move_to(point(center_x + radius, center_y));
-
-                        // Draw full circle using two 180-degree arcs
-                        progress_builder.arc_to(
-                            point(radius, radius),
-                            px(0.),
-                            false,
-                            true,
-                            point(center_x - radius, center_y),
-                        );
-                        progress_builder.arc_to(
-                            point(radius, radius),
-                            px(0.),
-                            false,
-                            true,
-                            point(center_x + radius, center_y),
-                        );
-                        progress_builder.close();
-                    } else {
-                        // Start at 12 o'clock (top) position
-                        let start_x = center_x;
-                        let start_y = center_y - radius;
-                        progress_builder.move_to(point(start_x, start_y));
-
-                        // Calculate the end point of the arc based on progress
-                        // Progress sweeps clockwise from -90° (top)
-                        let angle = -PI / 2.0 + (progress * 2.0 * PI);
-                        let end_x = center_x + radius * angle.cos();
-                        let end_y = center_y + radius * angle.sin();
-
-                        // Use large_arc flag when progress > 0.5 (more than 180 degrees)
-                        let large_arc = progress > 0.5;
-
-                        progress_builder.arc_to(
-                            point(radius, radius),
-                            px(0.),
-                            large_arc,
-                            true, // sweep clockwise
-                            point(end_x, end_y),
-                        );
-                    }
-
-                    if let Ok(path) = progress_builder.build() {
-                        window.paint_path(path, progress_color);
-                    }
-                }
-            },
-        )
-        .size(size)
+        if let Some((overflow, color)) = self.overflow
+            && let Some(path) = arc_path(
+                progress_arc(
+                    overflow,
+                    1.0,
+                    arc_start_angle(
+                        self.start_angle,
+                        self.direction,
+                        overflow,
+                        self.grow_from_center,
+                    ),
+                    self.direction,
+                    center,
+                    radius,
+                ),
+                center,
+                radius,
+                stroke_width,
+            )
+        {
+            window.paint_path(path, color);
+        }
+    }
+}
+
+/// Builds the stroke for a progress arc, or `None` when there's no progress to draw.
+fn arc_path(
+    arc: ProgressArc,
+    center: Point<Pixels>,
+    radius: Pixels,
+    stroke_width: Pixels,
+) -> Option<Path<Pixels>> {
+    match arc {
+        ProgressArc::None => None,
+        ProgressArc::Full => background_ring_path(center, radius, stroke_width),
+        ProgressArc::Partial {
+            start,
+            end,
+            large_arc,
+            sweep,
+        } => {
+            let mut progress_builder = PathBuilder::stroke(stroke_width);
+            progress_builder.move_to(start);
+            progress_builder.arc_to(point(radius, radius), px(0.), large_arc, sweep, end);
+            progress_builder.build().ok()
+        }
+    }
+}
+
+/// Past this many distinct ring sizes, the cache of background rings is cleared so it can't
+/// grow without bound.
+const MAX_CACHED_BACKGROUND_RINGS: usize = 32;
+
+thread_local! {
+    /// Full rings built around the origin, keyed by the bits of their radius and stroke width,
+    /// for backgrounds and complete progress alike. Tessellating the two arcs of a ring is the
+    /// bulk of painting one, so screens with many rings of the same size only move a copy of
+    /// the cached ring into place.
+    static BACKGROUND_RING_PATHS: RefCell<HashMap<(u32, u32), Path<Pixels>>> =
+        RefCell::new(HashMap::default());
+}
+
+fn background_ring_path(
+    center: Point<Pixels>,
+    radius: Pixels,
+    stroke_width: Pixels,
+) -> Option<Path<Pixels>> {
+    let key = (radius.as_f32().to_bits(), stroke_width.as_f32().to_bits());
+    BACKGROUND_RING_PATHS.with_borrow_mut(|paths| {
+        if !paths.contains_key(&key) {
+            if paths.len() >= MAX_CACHED_BACKGROUND_RINGS {
+                paths.clear();
+            }
+            paths.insert(key, build_background_ring(radius, stroke_width)?);
+        }
+        let mut path = paths.get(&key)?.clone();
+        for vertex in &mut path.vertices {
+            vertex.xy_position = vertex.xy_position + center;
+        }
+        path.bounds.origin = path.bounds.origin + center;
+        Some(path)
+    })
+}
+
+/// Builds a full ring centered on the origin, for the background and for complete progress.
+fn build_background_ring(radius: Pixels, stroke_width: Pixels) -> Option<Path<Pixels>> {
+    let mut bg_builder = PathBuilder::stroke(stroke_width);
+
+    // Start at rightmost point
+    bg_builder.move_to(point(radius, px(0.)));
+
+    // Draw full circle using two 180-degree arcs
+    bg_builder.arc_to(
+        point(radius, radius),
+        px(0.),
+        false,
+        true,
+        point(-radius, px(0.)),
+    );
+    bg_builder.arc_to(
+        point(radius, radius),
+        px(0.),
+        false,
+        true,
+        point(radius, px(0.)),
+    );
+    bg_builder.close();
+
+    bg_builder.build().ok()
+}
+
+/// Builds a filled circle, used for the start marker.
+fn build_dot(center: Point<Pixels>, radius: Pixels) -> Option<Path<Pixels>> {
+    let mut builder = PathBuilder::fill();
+    builder.move_to(point(center.x + radius, center.y));
+    builder.arc_to(
+        point(radius, radius),
+        px(0.),
+        false,
+        true,
+        point(center.x - radius, center.y),
+    );
+    builder.arc_to(
+        point(radius, radius),
+        px(0.),
+        false,
+        true,
+        point(center.x + radius, center.y),
+    );
+    builder.close();
+    builder.build().ok()
+}
+
+impl RenderOnce for CircularProgress {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        if let Some((id, delay)) = &self.show_after {
+            let first_shown = *window
+                .use_keyed_state((id.clone(), "first-shown"), cx, |_, _| Instant::now())
+                .read(cx);
+            if !delay_elapsed(first_shown, Instant::now(), *delay) {
+                window.request_animation_frame();
+                return div().size(self.size).into_any_element();
+            }
+        }
+
+        let stroke_width = self.resolved_stroke_width(self.size);
+        warn_if_stroke_too_wide(self.size, stroke_width);
+        let overflow = self
+            .over_color
+            .filter(|_| self.state == ProgressState::Active)
+            .and_then(|color| Some((overflow_fraction(self.value, self.max_value)?, color)));
+        let (value, max_value, fill, glyph) = match self.state {
+            ProgressState::Active => (self.drawn_value(), self.max_value, self.fill, None),
+            ProgressState::Success => (
+                1.0,
+                1.0,
+                ProgressFill::Solid(cx.theme().status().success),
+                Some((IconName::Check, Color::Success)),
+            ),
+            ProgressState::Failed => (
+                1.0,
+                1.0,
+                ProgressFill::Solid(cx.theme().status().error),
+                Some((IconName::Close, Color::Error)),
+            ),
+        };
+        let size = self.size;
+        let center = self.center.or_else(|| {
+            glyph.map(|(icon, color)| {
+                Icon::new(icon)
+                    .size(IconSize::Custom(rems_from_px(size / 2.0)))
+                    .color(color)
+                    .into_any_element()
+            })
+        });
+
+        let ring = RingPaint {
+            value,
+            max_value,
+            size,
+            start_angle: normalize_angle(self.start_angle),
+            direction: self.direction,
+            grow_from_center: self.grow_from_center,
+            start_marker: self.start_marker,
+            stroke_width,
+            stroke_align: self.stroke_align,
+            bg_color: self.bg_color,
+            fill,
+            overflow,
+        };
+        let rotation_id = self
+            .rotation_id
+            .filter(|_| self.state == ProgressState::Active);
+        let over_badge_label = over_badge_label(self.value, self.max_value)
+            .filter(|_| self.over_badge && self.state == ProgressState::Active);
+        let badge_bg = cx
+            .theme()
+            .colors()
+            .editor_background
+            .blend(cx.theme().status().error.opacity(0.4));
+
+        let ring = div()
+            .relative()
+            .size(size)
+            .map(|this| match rotation_id {
+                Some(id) => this.child(div().size_full().with_animation(
+                    id,
+                    Animation::new(ROTATION_PERIOD).repeat(),
+                    move |this, delta| this.child(ring.clone().rotated(delta).into_canvas()),
+                )),
+                None => this.child(ring.into_canvas()),
+            })
+            .when_some(center, |this, center| {
+                this.child(
+                    div()
+                        .absolute()
+                        .inset_0()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .child(center),
+                )
+            })
+            .when_some(over_badge_label, |this, label| {
+                this.child(
+                    h_flex()
+                        .absolute()
+                        .top_0()
+                        .right_0()
+                        .px_0p5()
+                        .h_3p5()
+                        .rounded_full()
+                        .bg(badge_bg)
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(
+                            Label::new(label)
+                                .size(LabelSize::Custom(rems_from_px(9.)))
+                                .weight(FontWeight::MEDIUM),
+                        ),
+                )
+            });
+
+        match self.show_after {
+            Some((id, _)) => ring
+                .with_animation(
+                    (id, "fade-in"),
+                    Animation::new(FADE_IN_DURATION),
+                    |this, delta| this.opacity(delta),
+                )
+                .into_any_element(),
+            None => ring.into_any_element(),
+        }
     }
 }
 
@@ -182,39 +1086,830 @@ impl Component for CircularProgress {
 
     fn preview(_window: &mut Window, cx: &mut App) -> AnyElement {
         let max_value = 100.0;
-        let container = || v_flex().items_center().gap_1();
-
-        example_group(vec![single_example(
-            "Examples",
-            h_flex()
-                .gap_6()
-                .child(
-                    container()
-                        .child(CircularProgress::new(0.0, max_value, px(48.0), cx))
-                        .child(Label::new("0%").size(LabelSize::Small)),
-                )
-                .child(
-                    container()
-                        .child(CircularProgress::new(25.0, max_value, px(48.0), cx))
-                        .child(Label::new("25%").size(LabelSize::Small)),
-                )
-                .child(
-                    container()
-                        .child(CircularProgress::new(50.0, max_value, px(48.0), cx))
-                        .child(Label::new("50%").size(LabelSize::Small)),
-                )
-                .child(
-                    container()
-                        .child(CircularProgress::new(75.0, max_value, px(48.0), cx))
-                        .child(Label::new("75%").size(LabelSize::Small)),
-                )
-                .child(
-                    container()
-                        .child(CircularProgress::new(100.0, max_value, px(48.0), cx))
-                        .child(Label::new("100%").size(LabelSize::Small)),
+        let ring = |value: f32| CircularProgress::new(value, max_value, px(48.0), cx);
+        let status = cx.theme().status();
+
+        // Each row is a titled set of labeled rings, so a new variant only needs an entry here.
+        let rows: Vec<(&'static str, Vec<(SharedString, CircularProgress)>)> = vec![
+            (
+                "Examples",
+                [0, 25, 50, 75, 100]
+                    .map(|percent| {
+                        (
+                            format!("{percent}%").into(),
+                            CircularProgress::percent(percent, px(48.0), cx),
+                        )
+                    })
+                    .into(),
+            ),
+            (
+                "From Fraction",
+                [0.1, 0.5, 0.9]
+                    .map(|fraction| {
+                        (
+                            format!("{fraction}").into(),
+                            CircularProgress::from_fraction(fraction, px(48.0), cx),
+                        )
+                    })
+                    .into(),
+            ),
+            (
+                "Colors",
+                vec![
+                    ("Track".into(), ring(60.0).bg_color(status.info_background)),
+                    ("Thin Stroke".into(), ring(60.0).stroke_width(px(2.0))),
+                    (
+                        "Outside Stroke".into(),
+                        ring(60.0)
+                            .stroke_width(px(8.0))
+                            .stroke_align(StrokeAlign::Outside),
+                    ),
+                    (
+                        "Proportional Stroke".into(),
+                        ring(60.0).size(px(64.0)).thickness_ratio(0.25),
+                    ),
+                    ("Success".into(), ring(60.0).progress_color(status.success)),
+                ],
+            ),
+            (
+                "States",
+                vec![
+                    ("Active".into(), ring(40.0)),
+                    ("Success".into(), ring(40.0).state(ProgressState::Success)),
+                    ("Failed".into(), ring(40.0).state(ProgressState::Failed)),
+                ],
+            ),
+            (
+                "Fills",
+                vec![
+                    ("Solid".into(), ring(75.0).fill(status.success)),
+                    (
+                        "Linear".into(),
+                        ring(75.0).fill(ProgressFill::Linear(status.info, status.success)),
+                    ),
+                    (
+                        "Conic".into(),
+                        ring(100.0).fill(ProgressFill::Conic(vec![
+                            (0.0, status.success),
+                            (0.6, status.warning),
+                            (1.0, status.error),
+                        ])),
+                    ),
+                ],
+            ),
+            (
+                "Center Content",
+                vec![
+                    (
+                        "Paused".into(),
+                        ring(40.0).center(Icon::new(IconName::DebugPause).size(IconSize::Small)),
+                    ),
+                    (
+                        "Overflowing".into(),
+                        ring(40.0)
+                            .size(px(16.0))
+                            .center(Icon::new(IconName::DebugPause).size(IconSize::Medium)),
+                    ),
+                ],
+            ),
+            (
+                "Counter-Clockwise",
+                [25.0, 60.0, 80.0]
+                    .map(|value| {
+                        (
+                            format!("{value}%").into(),
+                            ring(value).direction(ProgressDirection::CounterClockwise),
+                        )
+                    })
+                    .into(),
+            ),
+            (
+                "Countdown",
+                vec![
+                    ("0% Elapsed".into(), ring(0.0).countdown(true)),
+                    ("25% Elapsed".into(), ring(25.0).countdown(true)),
+                    (
+                        "Counter-Clockwise".into(),
+                        ring(25.0)
+                            .countdown(true)
+                            .direction(ProgressDirection::CounterClockwise),
+                    ),
+                    ("100% Elapsed".into(), ring(100.0).countdown(true)),
+                ],
+            ),
+            (
+                "Grow From Center",
+                [25.0, 75.0]
+                    .map(|value| {
+                        (
+                            format!("{value}%").into(),
+                            ring(value).grow_from_center(true),
+                        )
+                    })
+                    .into(),
+            ),
+            (
+                "Start Marker",
+                vec![
+                    ("0%".into(), ring(0.0).start_marker(true)),
+                    (
+                        "0% From 0°".into(),
+                        ring(0.0).start_marker(true).start_angle(0.0),
+                    ),
+                    ("40%".into(), ring(40.0).start_marker(true)),
+                ],
+            ),
+            (
+                "Rotating",
+                vec![
+                    ("Syncing 40%".into(), ring(40.0).rotating(true)),
+                    (
+                        "Counter-Clockwise".into(),
+                        ring(40.0)
+                            .direction(ProgressDirection::CounterClockwise)
+                            .rotating(true),
+                    ),
+                    (
+                        "Success".into(),
+                        ring(40.0).rotating(true).state(ProgressState::Success),
+                    ),
+                ],
+            ),
+            (
+                "Over Limit",
+                vec![
+                    ("120%".into(), ring(120.0).over_badge(true)),
+                    ("200%".into(), ring(200.0).over_badge(true)),
+                    (
+                        "140% Overlay".into(),
+                        ring(140.0).over_color(status.error).over_badge(true),
+                    ),
+                ],
+            ),
+            (
+                "Show After",
+                vec![(
+                    "Half a Second".into(),
+                    ring(40.0).show_after("show-after-preview", Duration::from_millis(500)),
+                )],
+            ),
+            (
+                "Start Angles",
+                [-90.0, 0.0, 90.0, 180.0]
+                    .map(|start_angle| {
+                        (
+                            format!("{start_angle}°").into(),
+                            ring(60.0).start_angle(start_angle),
+                        )
+                    })
+                    .into(),
+            ),
+        ];
+
+        let grid_items = (0..20)
+            .map(|ix| {
+                (
+                    ElementId::named_usize("grid-preview", ix),
+                    (ix * 5) as f32,
+                    max_value,
                 )
-                .into_any_element(),
-        )])
+            })
+            .collect::<Vec<_>>();
+
+        example_group(
+            rows.into_iter()
+                .map(|(title, examples)| {
+                    single_example(
+                        title,
+                        h_flex()
+                            .gap_6()
+                            .children(examples.into_iter().map(|(label, progress)| {
+                                v_flex()
+                                    .items_center()
+                                    .gap_1()
+                                    .child(progress)
+                                    .child(Label::new(label).size(LabelSize::Small))
+                            }))
+                            .into_any_element(),
+                    )
+                })
+                .chain([single_example(
+                    "Grid",
+                    div()
+                        .w(px(240.0))
+                        .child(CircularProgress::render_grid(&grid_items, px(20.0), cx))
+                        .into_any_element(),
+                )])
+                .collect(),
+        )
         .into_any_element()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    fn clockwise_arc(value: f32, max_value: f32) -> ProgressArc {
+        progress_arc(
+            value,
+            max_value,
+            DEFAULT_START_ANGLE,
+            ProgressDirection::Clockwise,
+            point(px(50.), px(50.)),
+            px(10.),
+        )
+    }
+
+    fn assert_points_eq(actual: Point<Pixels>, expected: Point<Pixels>) {
+        assert!(
+            (actual.x - expected.x).abs() < px(0.001) && (actual.y - expected.y).abs() < px(0.001),
+            "expected point {expected:?}, got {actual:?}"
+        );
+    }
+
+    fn assert_end_point(arc: ProgressArc, expected_end: Point<Pixels>, expected_large_arc: bool) {
+        let ProgressArc::Partial { end, large_arc, .. } = arc else {
+            panic!("expected a partial arc, got {arc:?}");
+        };
+        assert_points_eq(end, expected_end);
+        assert_eq!(large_arc, expected_large_arc);
+    }
+
+    #[test]
+    fn test_progress_arc() {
+        assert_eq!(clockwise_arc(0., 100.), ProgressArc::None);
+        assert_eq!(clockwise_arc(-5., 100.), ProgressArc::None);
+        assert_eq!(clockwise_arc(10., 0.), ProgressArc::Full);
+
+        assert_end_point(clockwise_arc(25., 100.), point(px(60.), px(50.)), false);
+        assert_end_point(clockwise_arc(50., 100.), point(px(50.), px(60.)), false);
+        assert_end_point(clockwise_arc(75., 100.), point(px(40.), px(50.)), true);
+    }
+
+    #[test]
+    fn test_progress_arc_full_ring_boundary() {
+        let center = point(px(50.), px(50.));
+        let radius = px(10.);
+
+        let ProgressArc::Partial { end, large_arc, .. } = clockwise_arc(99.8, 100.) else {
+            panic!("progress just below the threshold should draw an arc");
+        };
+        assert!(large_arc);
+        assert!(end.x < center.x && end.y < center.y - radius * 0.99);
+
+        assert_eq!(clockwise_arc(99.9, 100.), ProgressArc::Full);
+        assert_eq!(clockwise_arc(100., 100.), ProgressArc::Full);
+        assert_eq!(clockwise_arc(150., 100.), ProgressArc::Full);
+    }
+
+    #[test]
+    fn test_counter_clockwise_large_arc_across_start_angles() {
+        let center = point(px(50.), px(50.));
+        let radius = px(10.);
+
+        for start_angle in [-90., 0., 90., 180.] {
+            for (value, expected_large_arc) in [(40., false), (60., true), (80., true)] {
+                let arc = progress_arc(
+                    value,
+                    100.,
+                    start_angle,
+                    ProgressDirection::CounterClockwise,
+                    center,
+                    radius,
+                );
+                let ProgressArc::Partial {
+                    start,
+                    end,
+                    large_arc,
+                    sweep,
+                } = arc
+                else {
+                    panic!("expected a partial arc at {value}% from {start_angle}°, got {arc:?}");
+                };
+                assert_eq!(
+                    large_arc, expected_large_arc,
+                    "large arc flag at {value}% from {start_angle}°"
+                );
+                assert!(
+                    !sweep,
+                    "counter-clockwise arcs sweep in the negative direction"
+                );
+                assert_points_eq(start, angle_to_point(center, radius, start_angle));
+                assert_points_eq(
+                    end,
+                    angle_to_point(center, radius, start_angle - value / 100. * 360.),
+                );
+            }
+        }
+    }
+
+    fn test_progress(value: f32) -> CircularProgress {
+        CircularProgress {
+            value,
+            max_value: 100.,
+            size: px(48.),
+            start_angle: DEFAULT_START_ANGLE,
+            direction: ProgressDirection::Clockwise,
+            countdown: false,
+            grow_from_center: false,
+            start_marker: false,
+            stroke_width: Some(px(4.)),
+            thickness_ratio: None,
+            min_stroke_width: px(0.),
+            stroke_align: StrokeAlign::Center,
+            bg_color: gpui::black(),
+            fill: ProgressFill::Solid(gpui::white()),
+            center: None,
+            state: ProgressState::default(),
+            rotation_id: None,
+            over_badge: false,
+            over_color: None,
+            show_after: None,
+        }
+    }
+
+    #[test]
+    fn test_normalized_progress() {
+        assert_eq!(test_progress(-10.).normalized_progress(), 0.);
+        assert_eq!(test_progress(25.).normalized_progress(), 0.25);
+        assert_eq!(test_progress(150.).normalized_progress(), 1.);
+    }
+
+    #[test]
+    fn test_fraction() {
+        let progress = CircularProgress {
+            max_value: 1.,
+            ..test_progress(0.25)
+        };
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.normalized_progress(), 0.25);
+
+        let over_limit = test_progress(150.);
+        assert_eq!(over_limit.fraction(), 1.5);
+        assert_eq!(over_limit.normalized_progress(), 1.);
+    }
+
+    #[test]
+    fn test_default_colors_adjust_for_low_contrast_themes() {
+        for (colors, status) in [
+            (ThemeColors::dark(), StatusColors::dark()),
+            (ThemeColors::light(), StatusColors::light()),
+        ] {
+            assert_eq!(
+                default_colors(&colors, &status),
+                (colors.border_variant, status.info, px(0.))
+            );
+        }
+
+        // A high-contrast theme whose bright borders match its info color.
+        let status = StatusColors::dark();
+        let colors = ThemeColors {
+            border_variant: status.info,
+            ..ThemeColors::dark()
+        };
+        let (track, fill, min_stroke_width) = default_colors(&colors, &status);
+        assert_eq!(track, status.info);
+        assert_ne!(fill, status.info);
+        assert!(apca_contrast(fill, track).abs() >= ADJUSTED_FILL_CONTRAST);
+        assert_eq!(min_stroke_width, ADJUSTED_MIN_STROKE_WIDTH);
+    }
+
+    #[test]
+    fn test_countdown_draws_the_remaining_fraction() {
+        for value in [-10., 0., 25., 60., 100., 150.] {
+            let countdown = CircularProgress {
+                countdown: true,
+                ..test_progress(value)
+            };
+            let complement = 1. - test_progress(value).normalized_progress();
+            assert!(
+                (countdown.normalized_progress() - complement).abs() < 0.001,
+                "value {value}"
+            );
+            assert_eq!(countdown.fraction(), test_progress(value).fraction());
+        }
+
+        for direction in [
+            ProgressDirection::Clockwise,
+            ProgressDirection::CounterClockwise,
+        ] {
+            let countdown = CircularProgress {
+                countdown: true,
+                ..test_progress(25.).direction(direction).start_angle(30.)
+            };
+            assert_points_eq(
+                countdown.progress_end_point(px(48.)),
+                test_progress(75.)
+                    .direction(direction)
+                    .start_angle(30.)
+                    .progress_end_point(px(48.)),
+            );
+        }
+    }
+
+    #[gpui::test]
+    fn test_percent_clamps_before_construction(cx: &mut TestAppContext) {
+        cx.update(|cx| theme::init(theme::LoadThemes::JustBase, cx));
+
+        let progress = cx.update(|cx| CircularProgress::percent(40, px(48.), cx));
+        assert_eq!((progress.value, progress.max_value), (40., 100.));
+
+        let progress = cx.update(|cx| CircularProgress::percent(150, px(48.), cx));
+        assert_eq!((progress.value, progress.max_value), (100., 100.));
+        assert_eq!(progress.fraction(), 1.);
+        assert_eq!(over_badge_label(progress.value, progress.max_value), None);
+    }
+
+    #[test]
+    fn test_delay_elapsed() {
+        let first_shown = Instant::now();
+        let delay = Duration::from_millis(200);
+        assert!(!delay_elapsed(first_shown, first_shown, delay));
+        assert!(!delay_elapsed(
+            first_shown,
+            first_shown + Duration::from_millis(199),
+            delay
+        ));
+        assert!(delay_elapsed(first_shown, first_shown + delay, delay));
+        assert!(delay_elapsed(
+            first_shown,
+            first_shown + Duration::from_secs(1),
+            delay
+        ));
+        assert!(delay_elapsed(first_shown, first_shown, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let path_data = |value: f32| {
+            let svg = test_progress(value).to_svg(px(48.));
+            svg.split(r#"<path d=""#)
+                .nth(1)
+                .and_then(|path| path.split('"').next())
+                .map(str::to_string)
+        };
+
+        assert_eq!(path_data(0.), None);
+        assert_eq!(
+            path_data(25.).as_deref(),
+            Some("M 24 4 A 20 20 0 0 1 44 24")
+        );
+        assert_eq!(
+            path_data(50.).as_deref(),
+            Some("M 24 4 A 20 20 0 0 1 24 44")
+        );
+        assert_eq!(path_data(75.).as_deref(), Some("M 24 4 A 20 20 0 1 1 4 24"));
+        assert_eq!(
+            path_data(100.).as_deref(),
+            Some("M 44 24 A 20 20 0 0 1 4 24 A 20 20 0 0 1 44 24 Z")
+        );
+
+        assert_eq!(
+            test_progress(25.).to_svg(px(48.)),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="48" viewBox="0 0 48 48">"#,
+                r##"<circle cx="24" cy="24" r="20" fill="none" stroke="#000000ff" stroke-width="4"/>"##,
+                r##"<path d="M 24 4 A 20 20 0 0 1 44 24" fill="none" stroke="#ffffffff" stroke-width="4"/>"##,
+                "</svg>"
+            )
+        );
+
+        let counter_clockwise = test_progress(25.).direction(ProgressDirection::CounterClockwise);
+        assert!(
+            counter_clockwise
+                .to_svg(px(48.))
+                .contains(r#"d="M 24 4 A 20 20 0 0 0 4 24""#)
+        );
+    }
+
+    #[test]
+    fn test_diameter() {
+        assert_eq!(test_progress(50.).diameter(), px(48.));
+        assert_eq!(test_progress(50.).size(px(20.)).diameter(), px(20.));
+    }
+
+    #[test]
+    fn test_over_badge_label() {
+        assert_eq!(over_badge_label(50., 100.), None);
+        assert_eq!(over_badge_label(100., 100.), None);
+        assert_eq!(over_badge_label(100.2, 100.), Some("+1%".into()));
+        assert_eq!(over_badge_label(120., 100.), Some("+20%".into()));
+        assert_eq!(over_badge_label(200., 100.), Some("+100%".into()));
+        assert_eq!(over_badge_label(1.5, 1.), Some("+50%".into()));
+        assert_eq!(over_badge_label(10., 0.), None);
+    }
+
+    #[test]
+    fn test_overflow_fraction() {
+        assert_eq!(overflow_fraction(50., 100.), None);
+        assert_eq!(overflow_fraction(100., 100.), None);
+        assert!(
+            overflow_fraction(140., 100.).is_some_and(|overflow| (overflow - 0.4).abs() < 0.001)
+        );
+        assert_eq!(overflow_fraction(300., 100.), Some(1.));
+    }
+
+    #[test]
+    fn test_progress_end_point() {
+        // A 48px ring with a 4px stroke is centered at (24, 24) with a 20px radius.
+        assert_points_eq(
+            test_progress(0.).progress_end_point(px(48.)),
+            point(px(24.), px(4.)),
+        );
+        assert_points_eq(
+            test_progress(50.).progress_end_point(px(48.)),
+            point(px(24.), px(44.)),
+        );
+        assert_points_eq(
+            test_progress(100.).progress_end_point(px(48.)),
+            point(px(24.), px(4.)),
+        );
+        assert_points_eq(
+            test_progress(25.)
+                .direction(ProgressDirection::CounterClockwise)
+                .progress_end_point(px(48.)),
+            point(px(4.), px(24.)),
+        );
+    }
+
+    #[test]
+    fn test_start_point() {
+        let diameter = px(48.);
+        let center = point(px(24.), px(24.));
+        for start_angle in [DEFAULT_START_ANGLE, 0., 135.] {
+            let progress = test_progress(0.)
+                .start_marker(true)
+                .start_angle(start_angle);
+            assert_points_eq(
+                progress.start_point(diameter),
+                angle_to_point(center, px(20.), start_angle),
+            );
+            assert_points_eq(
+                progress.start_point(diameter),
+                progress.progress_end_point(diameter),
+            );
+        }
+
+        let outside = test_progress(0.)
+            .start_angle(0.)
+            .stroke_align(StrokeAlign::Outside);
+        assert_points_eq(outside.start_point(diameter), point(px(46.), px(24.)));
+    }
+
+    #[test]
+    fn test_grow_from_center() {
+        let diameter = px(48.);
+        let center = point(px(24.), px(24.));
+
+        // A quarter of the ring centered on the top spans from -135° to -45°.
+        assert_eq!(
+            arc_start_angle(
+                DEFAULT_START_ANGLE,
+                ProgressDirection::Clockwise,
+                0.25,
+                true
+            ),
+            225.
+        );
+        let centered = test_progress(25.).grow_from_center(true);
+        assert_points_eq(
+            centered.progress_end_point(diameter),
+            angle_to_point(center, px(20.), -45.),
+        );
+        assert_points_eq(centered.start_point(diameter), point(px(24.), px(4.)));
+        assert!(centered.to_svg(diameter).contains(&format!(
+            "M {} {} A 20 20 0 0 1 {} {}",
+            svg_number(px(24.) - px(20.) * 0.5f32.sqrt()),
+            svg_number(px(24.) - px(20.) * 0.5f32.sqrt()),
+            svg_number(px(24.) + px(20.) * 0.5f32.sqrt()),
+            svg_number(px(24.) - px(20.) * 0.5f32.sqrt()),
+        )));
+
+        let counter_clockwise = centered.direction(ProgressDirection::CounterClockwise);
+        assert_eq!(
+            arc_start_angle(
+                DEFAULT_START_ANGLE,
+                ProgressDirection::CounterClockwise,
+                0.25,
+                true
+            ),
+            315.
+        );
+        assert_points_eq(
+            counter_clockwise.progress_end_point(diameter),
+            angle_to_point(center, px(20.), -135.),
+        );
+
+        assert_eq!(
+            arc_start_angle(
+                DEFAULT_START_ANGLE,
+                ProgressDirection::Clockwise,
+                0.25,
+                false
+            ),
+            DEFAULT_START_ANGLE
+        );
+    }
+
+    #[test]
+    fn test_normalize_angle() {
+        assert_eq!(normalize_angle(0.), 0.);
+        assert_eq!(normalize_angle(-90.), 270.);
+        assert_eq!(normalize_angle(450.), 90.);
+        assert_eq!(normalize_angle(360.), 0.);
+        assert_eq!(normalize_angle(-720.), 0.);
+        assert_eq!(normalize_angle(-1e-6), 0.);
+        assert_eq!(
+            end_angle(-90., ProgressDirection::CounterClockwise, 0.5),
+            90.
+        );
+    }
+
+    #[test]
+    fn test_fill_segments() {
+        assert!(fill_segments(DEFAULT_START_ANGLE, ProgressDirection::Clockwise, 0.).is_empty());
+
+        let quarter = fill_segments(DEFAULT_START_ANGLE, ProgressDirection::Clockwise, 0.25);
+        assert_eq!(quarter.len(), 18);
+        assert_eq!(quarter[0].start_angle, 270.);
+        assert_eq!(quarter[0].position, 2.5 / 360.);
+        assert_eq!(quarter[17].end_angle, 0.);
+        assert!(
+            quarter
+                .windows(2)
+                .all(|pair| pair[0].end_angle == pair[1].start_angle),
+            "segments should be contiguous"
+        );
+
+        let counter_clockwise = fill_segments(0., ProgressDirection::CounterClockwise, 0.1);
+        assert_eq!(counter_clockwise.len(), 8);
+        assert_eq!(counter_clockwise[7].end_angle, 324.);
+
+        let full = fill_segments(DEFAULT_START_ANGLE, ProgressDirection::Clockwise, 0.9995);
+        assert_eq!(full.len(), 72);
+        assert_eq!(full[71].end_angle, 270.);
+        assert!(full[71].position < 1.);
+    }
+
+    #[test]
+    fn test_stroke_align_radius() {
+        // A 48px ring with a 4px stroke has its nominal circle at a 20px radius.
+        let diameter = px(48.);
+        let stroke_width = px(4.);
+        assert_eq!(
+            arc_radius(diameter, stroke_width, StrokeAlign::Center),
+            px(20.)
+        );
+        assert_eq!(
+            arc_radius(diameter, stroke_width, StrokeAlign::Inside),
+            px(18.)
+        );
+        assert_eq!(
+            arc_radius(diameter, stroke_width, StrokeAlign::Outside) + stroke_width / 2.,
+            diameter / 2.,
+            "an outside stroke's outer edge is flush with the bounds"
+        );
+        assert_eq!(
+            arc_radius(px(12.), px(4.), StrokeAlign::Inside),
+            px(2.),
+            "an inside stroke never crosses the center"
+        );
+
+        assert_points_eq(
+            test_progress(0.)
+                .stroke_align(StrokeAlign::Outside)
+                .progress_end_point(diameter),
+            point(px(24.), px(2.)),
+        );
+    }
+
+    #[test]
+    fn test_cached_background_ring_matches_a_fresh_one() {
+        let center = point(px(24.), px(24.));
+        let radius = px(20.);
+        let stroke_width = px(4.);
+
+        let mut builder = PathBuilder::stroke(stroke_width);
+        builder.move_to(point(center.x + radius, center.y));
+        builder.arc_to(
+            point(radius, radius),
+            px(0.),
+            false,
+            true,
+            point(center.x - radius, center.y),
+        );
+        builder.arc_to(
+            point(radius, radius),
+            px(0.),
+            false,
+            true,
+            point(center.x + radius, center.y),
+        );
+        builder.close();
+        let fresh = builder.build().unwrap();
+
+        // The second lookup is served from the cache.
+        for _ in 0..2 {
+            let cached = background_ring_path(center, radius, stroke_width).unwrap();
+            assert_eq!(cached.vertices.len(), fresh.vertices.len());
+            for (cached_vertex, fresh_vertex) in cached.vertices.iter().zip(&fresh.vertices) {
+                assert_points_eq(cached_vertex.xy_position, fresh_vertex.xy_position);
+            }
+            assert_points_eq(cached.bounds.origin, fresh.bounds.origin);
+        }
+    }
+
+    #[test]
+    fn test_thickness_ratio_scales_with_the_ring() {
+        let proportional = CircularProgress {
+            stroke_width: None,
+            ..test_progress(0.)
+        }
+        .thickness_ratio(0.2);
+        assert_eq!(proportional.resolved_stroke_width(px(20.)), px(2.));
+        assert_eq!(proportional.resolved_stroke_width(px(40.)), px(4.));
+
+        let explicit = test_progress(0.).thickness_ratio(0.2);
+        assert_eq!(explicit.resolved_stroke_width(px(40.)), px(4.));
+        let explicit = test_progress(0.).stroke_width(px(6.)).thickness_ratio(0.2);
+        assert_eq!(explicit.resolved_stroke_width(px(40.)), px(6.));
+    }
+
+    #[test]
+    fn test_oversized_stroke_is_clamped() {
+        let diameter = px(12.);
+        let stroke_width = clamp_stroke_width(diameter, px(20.));
+        assert_eq!(stroke_width, px(4.));
+        assert!(arc_radius(diameter, stroke_width, StrokeAlign::Center) > px(0.));
+        assert_eq!(
+            arc_radius(diameter, stroke_width, StrokeAlign::Center) - stroke_width / 2.,
+            px(0.),
+            "the widest stroke fills the ring up to its center"
+        );
+
+        assert_eq!(clamp_stroke_width(diameter, px(2.)), px(2.));
+        assert_eq!(clamp_stroke_width(diameter, px(-1.)), px(0.));
+
+        let progress = test_progress(50.).stroke_width(px(20.));
+        assert_points_eq(progress.progress_end_point(diameter), point(px(6.), px(8.)));
+    }
+
+    #[test]
+    fn test_counter_clockwise_mirrors_clockwise() {
+        let center = point(px(50.), px(50.));
+        let radius = px(10.);
+
+        // Mirrored across the vertical axis through the top of the ring.
+        let ProgressArc::Partial { end, sweep, .. } = progress_arc(
+            60.,
+            100.,
+            DEFAULT_START_ANGLE,
+            ProgressDirection::CounterClockwise,
+            center,
+            radius,
+        ) else {
+            panic!("expected a partial arc");
+        };
+        let ProgressArc::Partial {
+            end: clockwise_end,
+            sweep: clockwise_sweep,
+            ..
+        } = clockwise_arc(60., 100.)
+        else {
+            panic!("expected a partial arc");
+        };
+        assert!(clockwise_sweep && !sweep);
+        assert_points_eq(end, point(center.x * 2. - clockwise_end.x, clockwise_end.y));
+    }
+
+    #[test]
+    fn test_rotation_follows_direction_and_keeps_progress() {
+        let ring = |direction| RingPaint {
+            value: 40.,
+            max_value: 100.,
+            size: px(48.),
+            start_angle: DEFAULT_START_ANGLE,
+            direction,
+            grow_from_center: false,
+            start_marker: false,
+            stroke_width: px(4.),
+            stroke_align: StrokeAlign::Center,
+            bg_color: gpui::black(),
+            fill: ProgressFill::Solid(gpui::white()),
+            overflow: None,
+        };
+
+        let clockwise = ring(ProgressDirection::Clockwise).rotated(0.25);
+        assert_eq!(clockwise.start_angle, 0.);
+        assert_eq!(
+            normalized_progress(clockwise.value, clockwise.max_value),
+            0.4
+        );
+
+        let counter_clockwise = ring(ProgressDirection::CounterClockwise).rotated(0.25);
+        assert_eq!(counter_clockwise.start_angle, 180.);
+    }
+}
@@ -0,0 +1,141 @@
+use gpui::{Hsla, Rgba};
+
+/// Describes how a progress component colors the part of its track that is filled.
+///
+/// Gradient positions are fractions from `0.0` to `1.0` of the whole track, measured in the
+/// direction progress grows, so each point keeps its color as progress advances. For a
+/// [`CircularProgress`](crate::CircularProgress) that is a fraction of a full turn from its
+/// start angle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressFill {
+    Solid(Hsla),
+    /// Blends from the first color at the start of the track to the second at its end.
+    Linear(Hsla, Hsla),
+    /// Blends between `(position, color)` stops, which suits a circular sweep. Positions
+    /// before the first stop or after the last take that stop's color.
+    Conic(Vec<(f32, Hsla)>),
+}
+
+impl ProgressFill {
+    /// Returns the color at `position`, a fraction of the track between `0.0` and `1.0`.
+    pub fn color_at(&self, position: f32) -> Hsla {
+        let position = position.clamp(0.0, 1.0);
+        match self {
+            ProgressFill::Solid(color) => *color,
+            ProgressFill::Linear(from, to) => mix(*from, *to, position),
+            ProgressFill::Conic(stops) => {
+                let mut stops = stops.clone();
+                stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                let Some(&(first_position, first_color)) = stops.first() else {
+                    return gpui::transparent_black();
+                };
+                if position <= first_position {
+                    return first_color;
+                }
+                stops
+                    .windows(2)
+                    .find_map(|window| {
+                        let [(from_position, from), (to_position, to)] = *window else {
+                            return None;
+                        };
+                        (position <= to_position).then(|| {
+                            let span = to_position - from_position;
+                            if span <= 0.0 {
+                                to
+                            } else {
+                                mix(from, to, (position - from_position) / span)
+                            }
+                        })
+                    })
+                    .unwrap_or_else(|| stops[stops.len() - 1].1)
+            }
+        }
+    }
+
+    /// Whether every position has the same color, so the track can be filled in one pass.
+    pub fn is_solid(&self) -> bool {
+        matches!(self, ProgressFill::Solid(_))
+    }
+}
+
+impl From<Hsla> for ProgressFill {
+    fn from(color: Hsla) -> Self {
+        ProgressFill::Solid(color)
+    }
+}
+
+/// Blends in RGB space, so colors with distant hues don't sweep through the hues between them.
+fn mix(from: Hsla, to: Hsla, amount: f32) -> Hsla {
+    let from = Rgba::from(from);
+    let to = Rgba::from(to);
+    let lerp = |from: f32, to: f32| from + (to - from) * amount;
+    Rgba {
+        r: lerp(from.r, to.r),
+        g: lerp(from.g, to.g),
+        b: lerp(from.b, to.b),
+        a: lerp(from.a, to.a),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::rgba;
+
+    use super::*;
+
+    fn assert_color_eq(actual: Hsla, expected: u32) {
+        let actual = Rgba::from(actual);
+        let expected = rgba(expected);
+        assert!(
+            (actual.r - expected.r).abs() < 0.01
+                && (actual.g - expected.g).abs() < 0.01
+                && (actual.b - expected.b).abs() < 0.01
+                && (actual.a - expected.a).abs() < 0.01,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn test_conic_stop_interpolation() {
+        let fill = ProgressFill::Conic(vec![
+            (0.5, rgba(0x0000ffff).into()),
+            (0.0, rgba(0xff0000ff).into()),
+            (0.75, rgba(0x00ff00ff).into()),
+        ]);
+
+        // 45° and 135° of a full turn fall between the first two stops.
+        assert_color_eq(fill.color_at(45.0 / 360.0), 0xbf0040ff);
+        assert_color_eq(fill.color_at(135.0 / 360.0), 0x4000bfff);
+        assert_color_eq(fill.color_at(0.5), 0x0000ffff);
+        assert_color_eq(fill.color_at(225.0 / 360.0), 0x008080ff);
+        assert_color_eq(fill.color_at(300.0 / 360.0), 0x00ff00ff);
+        assert_color_eq(fill.color_at(1.5), 0x00ff00ff);
+        assert_color_eq(fill.color_at(-1.0), 0xff0000ff);
+
+        let hard_stop = ProgressFill::Conic(vec![
+            (0.5, rgba(0xff0000ff).into()),
+            (0.5, rgba(0x0000ffff).into()),
+        ]);
+        assert_color_eq(hard_stop.color_at(0.25), 0xff0000ff);
+        assert_color_eq(hard_stop.color_at(0.75), 0x0000ffff);
+
+        assert!(
+            ProgressFill::Conic(Vec::new())
+                .color_at(0.5)
+                .is_transparent()
+        );
+    }
+
+    #[test]
+    fn test_linear_and_solid_colors() {
+        let linear = ProgressFill::Linear(rgba(0x000000ff).into(), rgba(0xffffffff).into());
+        assert_color_eq(linear.color_at(0.0), 0x000000ff);
+        assert_color_eq(linear.color_at(0.5), 0x808080ff);
+        assert_color_eq(linear.color_at(1.0), 0xffffffff);
+
+        let solid = ProgressFill::from(Hsla::from(rgba(0x336699ff)));
+        assert!(solid.is_solid());
+        assert_color_eq(solid.color_at(0.3), 0x336699ff);
+    }
+}
@@ -8,6 +8,7 @@ mod color_contrast;
 mod constants;
 mod corner_solver;
 mod format_distance;
+mod fuzzy_match;
 mod search_input;
 mod with_rem_size;
 
@@ -16,6 +17,7 @@ pub use color_contrast::*;
 pub use constants::*;
 pub use corner_solver::{CornerSolver, inner_corner_radius};
 pub use format_distance::*;
+pub use fuzzy_match::*;
 pub use search_input::*;
 pub use with_rem_size::*;
 
@@ -0,0 +1,134 @@
+/// Each matched character is worth this much.
+const MATCH_SCORE: i32 = 1;
+/// Bonus for a match that directly follows the previous one.
+const CONTIGUOUS_BONUS: i32 = 5;
+/// Bonus for a match at the start of a word, such as the `l` in `One Light` or `oneLight`.
+const WORD_START_BONUS: i32 = 3;
+/// Extra bonus when the first query character matches the start of the candidate.
+const PREFIX_BONUS: i32 = 4;
+/// Penalty for each candidate character skipped between two matches.
+const GAP_PENALTY: i32 = 1;
+
+/// Scores how well `query` fuzzy-matches `candidate`, or returns `None` if it doesn't match.
+///
+/// The query matches when its characters appear in the candidate in order, ignoring case
+/// and whitespace in the query. Higher scores are better matches: contiguous runs, matches at
+/// word starts and a match at the very start of the candidate all score higher, while gaps
+/// between matched characters score lower. An empty query matches everything with a score of 0.
+pub fn score_match(query: &str, candidate: &str) -> Option<i32> {
+    let query = query
+        .chars()
+        .filter(|query_char| !query_char.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut candidate_chars = Vec::new();
+    let mut previous = None;
+    for candidate_char in candidate.chars() {
+        let word_start = match previous {
+            None => true,
+            Some(previous) => {
+                !char::is_alphanumeric(previous)
+                    || (previous.is_lowercase() && candidate_char.is_uppercase())
+            }
+        };
+        for (ix, lowercase) in candidate_char.to_lowercase().enumerate() {
+            candidate_chars.push((lowercase, word_start && ix == 0));
+        }
+        previous = Some(candidate_char);
+    }
+
+    // best_scores[j] is the best score for the query so far with its last character matched
+    // at candidate position j.
+    let mut best_scores: Vec<Option<i32>> = vec![None; candidate_chars.len()];
+    for (query_ix, query_char) in query.iter().enumerate() {
+        let mut next_scores = vec![None; candidate_chars.len()];
+        for (candidate_ix, (candidate_char, word_start)) in candidate_chars.iter().enumerate() {
+            if candidate_char != query_char {
+                continue;
+            }
+            let mut bonus = MATCH_SCORE;
+            if *word_start {
+                bonus += WORD_START_BONUS;
+            }
+            if query_ix == 0 {
+                if candidate_ix == 0 {
+                    bonus += PREFIX_BONUS;
+                }
+                next_scores[candidate_ix] = Some(bonus);
+                continue;
+            }
+            next_scores[candidate_ix] = best_scores[..candidate_ix]
+                .iter()
+                .enumerate()
+                .filter_map(|(previous_ix, score)| {
+                    let gap = (candidate_ix - previous_ix - 1) as i32;
+                    let transition = if gap == 0 {
+                        CONTIGUOUS_BONUS
+                    } else {
+                        -gap * GAP_PENALTY
+                    };
+                    Some((*score)? + transition)
+                })
+                .max()
+                .map(|score| score + bonus);
+        }
+        best_scores = next_scores;
+    }
+
+    best_scores.into_iter().flatten().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_match_filters_non_matches() {
+        assert_eq!(score_match("", "anything"), Some(0));
+        assert!(score_match("ol", "One Light").is_some());
+        assert!(score_match("ONE light", "One Light").is_some());
+        assert_eq!(score_match("lo", "One Light"), None);
+        assert_eq!(score_match("dark", "One Light"), None);
+        assert_eq!(score_match("one light", "One"), None);
+    }
+
+    #[test]
+    fn test_score_match_ranking() {
+        let rank = |query: &str, candidates: &[&'static str]| {
+            let mut ranked = candidates
+                .iter()
+                .filter_map(|candidate| Some((score_match(query, candidate)?, *candidate)))
+                .collect::<Vec<_>>();
+            ranked.sort_by(|(a, _), (b, _)| b.cmp(a));
+            ranked
+                .into_iter()
+                .map(|(_, candidate)| candidate)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rank("fb", &["fizzbuzz", "foobar"]), ["foobar", "fizzbuzz"]);
+        assert_eq!(
+            rank("light", &["Gruvbox Lite Hard", "One Light", "Highlight"]),
+            ["One Light", "Highlight"]
+        );
+        assert_eq!(
+            rank("sel", &["Unselected", "Selection"]),
+            ["Selection", "Unselected"]
+        );
+        assert_eq!(
+            rank("gd", &["goToDefinition", "grid"]),
+            ["goToDefinition", "grid"]
+        );
+    }
+
+    #[test]
+    fn test_score_match_prefers_best_alignment() {
+        // A greedy match would take the first `a` and leave a gap before `b`.
+        assert!(score_match("ab", "a-xab") > score_match("ab", "a-xxb"));
+        assert!(score_match("ab", "xab") > score_match("ab", "xaxb"));
+    }
+}
@@ -11,6 +11,7 @@ use std::path::StripPrefixError;
 use std::sync::Arc;
 use std::{
     ffi::OsStr,
+    ops::Range,
     path::{Path, PathBuf},
     sync::LazyLock,
 };
@@ -18,9 +19,20 @@ use std::{
 use crate::rel_path::RelPath;
 use crate::rel_path::RelPathBuf;
 
+static HOME_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/// Overrides the directory returned by [`home_dir`], e.g. for tests or sandboxes where `$HOME`
+/// is unusual.
+///
+/// The home directory is resolved once per process, so the override must be set before the
+/// first call to [`home_dir`] (including indirect ones like [`PathExt::compact`]). Returns the
+/// rejected path if the home directory has already been resolved.
+pub fn set_home_dir_override(path: PathBuf) -> Result<(), PathBuf> {
+    HOME_DIR.set(path)
+}
+
 /// Returns the path to the user's home directory.
 pub fn home_dir() -> &'static PathBuf {
-    static HOME_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
     HOME_DIR.get_or_init(|| {
         if cfg!(any(test, feature = "test-support")) {
             if cfg!(target_os = "macos") {
@@ -44,12 +56,74 @@ pub trait PathExt {
     ///
     /// * A `PathBuf` containing the compacted file path. If the input path
     ///   does not have the user's home directory prefix, or if we are not on
-    ///   Linux or macOS, the original path is returned unchanged.
+    ///   Linux, macOS or Windows, the original path is returned unchanged.
     fn compact(&self) -> PathBuf;
 
+    /// Like [`PathExt::compact`], but borrows the original path instead of copying it when
+    /// there's no home directory prefix to replace.
+    fn compact_cow(&self) -> Cow<'_, Path>;
+
+    /// Compacts a path against a list of known roots, each paired with the display prefix that
+    /// replaces it (e.g. a project directory mapped to `""` or to the project's name).
+    ///
+    /// Roots are tried in order, so more specific roots should come first. If none of them
+    /// match, this falls back to [`PathExt::compact`].
+    fn compact_against(&self, roots: &[(&Path, &str)]) -> PathBuf;
+
+    /// Shortens a path for logs by replacing the longest matching prefix with its token, such
+    /// as a cargo registry directory with `<registry>`, so messages read the same on every
+    /// machine.
+    ///
+    /// Unlike [`PathExt::compact_against`], the most specific prefix wins whatever its position,
+    /// and a path matching none of them is returned verbatim rather than compacted.
+    fn strip_known_prefixes(&self, prefixes: &[(&Path, &str)]) -> String;
+
+    /// Expands a leading tilde (`~`) component into the user's home directory.
+    /// The inverse of [`PathExt::compact`].
+    ///
+    /// Only a bare `~` component is expanded; `~user` forms are left unchanged, as are paths
+    /// that don't start with a tilde.
+    fn expand_tilde(&self) -> PathBuf;
+
+    /// Lexically collapses `.` and `..` components without touching the filesystem, so unlike
+    /// [`Path::canonicalize`] it works for paths that don't exist.
+    ///
+    /// Leading `..` components of a relative path are preserved, while `..` components that
+    /// would climb above the root or prefix of an absolute path are dropped.
+    fn normalize(&self) -> PathBuf;
+
+    /// Returns the path relative to `base`, or `None` if it isn't `base` or one of its
+    /// descendants. An exact match yields an empty relative path.
+    ///
+    /// Both paths are sanitized first, so UNC prefixes on Windows don't break the match.
+    fn relativize(&self, base: &Path) -> Option<PathBuf>;
+
+    /// Returns whether the path is strictly inside `ancestor`, comparing whole components so
+    /// that `a/bc` isn't inside `a/b`. A path isn't its own descendant.
+    ///
+    /// Both paths are sanitized first, so UNC prefixes on Windows don't break the check.
+    fn is_descendant_of(&self, ancestor: &Path) -> bool;
+
+    /// Returns whether `descendant` is strictly inside the path. The inverse of
+    /// [`PathExt::is_descendant_of`].
+    fn is_ancestor_of(&self, descendant: &Path) -> bool;
+
+    /// Walks up from the path, starting with the path itself, to the nearest ancestor that
+    /// contains an entry named `marker` (such as `.git`), and returns that ancestor along with
+    /// the path relative to it.
+    ///
+    /// Unlike the other methods here this touches the filesystem, checking for `marker` at each
+    /// level. Returns `None` if no ancestor contains it.
+    fn relative_to_marker(&self, marker: &str) -> Option<(PathBuf, PathBuf)>;
+
     /// Returns a file's extension or, if the file is hidden, its name without the leading dot
     fn extension_or_hidden_file_name(&self) -> Option<&str>;
 
+    /// Returns the longest of `known_compound_extensions` (e.g. `tar.gz` or `d.ts`, without a
+    /// leading dot) that the file name ends with, falling back to
+    /// [`PathExt::extension_or_hidden_file_name`] when none of them match.
+    fn compound_extension(&self, known_compound_extensions: &[&str]) -> Option<&str>;
+
     fn try_from_bytes<'a>(bytes: &'a [u8]) -> anyhow::Result<Self>
     where
         Self: From<&'a Path>,
@@ -98,9 +172,13 @@ pub trait PathExt {
 
 impl<T: AsRef<Path>> PathExt for T {
     fn compact(&self) -> PathBuf {
+        self.compact_cow().into_owned()
+    }
+
+    fn compact_cow(&self) -> Cow<'_, Path> {
         #[cfg(target_family = "wasm")]
         {
-            self.as_ref().to_path_buf()
+            Cow::Borrowed(self.as_ref())
         }
         #[cfg(not(target_family = "wasm"))]
         if cfg!(any(target_os = "linux", target_os = "freebsd")) || cfg!(target_os = "macos") {
@@ -109,13 +187,112 @@ impl<T: AsRef<Path>> PathExt for T {
                     let mut shortened_path = PathBuf::new();
                     shortened_path.push("~");
                     shortened_path.push(relative_path);
-                    shortened_path
+                    Cow::Owned(shortened_path)
+                }
+                Err(_) => Cow::Borrowed(self.as_ref()),
+            }
+        } else if cfg!(target_os = "windows") {
+            match SanitizedPath::new(self.as_ref()).strip_prefix(SanitizedPath::new(home_dir())) {
+                Ok(relative_path) => {
+                    let mut shortened_path = PathBuf::new();
+                    shortened_path.push("~");
+                    shortened_path.push(relative_path);
+                    Cow::Owned(shortened_path)
                 }
-                Err(_) => self.as_ref().to_path_buf(),
+                Err(_) => Cow::Borrowed(self.as_ref()),
             }
         } else {
-            self.as_ref().to_path_buf()
+            Cow::Borrowed(self.as_ref())
+        }
+    }
+
+    fn compact_against(&self, roots: &[(&Path, &str)]) -> PathBuf {
+        roots
+            .iter()
+            .find_map(|(root, display_prefix)| {
+                let relative_path = self.relativize(root)?;
+                Some(Path::new(display_prefix).join(relative_path))
+            })
+            .unwrap_or_else(|| self.compact())
+    }
+
+    fn strip_known_prefixes(&self, prefixes: &[(&Path, &str)]) -> String {
+        prefixes
+            .iter()
+            .filter_map(|(prefix, token)| {
+                let relative_path = self.relativize(prefix)?;
+                Some((prefix.components().count(), token, relative_path))
+            })
+            .max_by_key(|(prefix_length, ..)| *prefix_length)
+            .map(|(_, token, relative_path)| {
+                if relative_path.as_os_str().is_empty() {
+                    token.to_string()
+                } else {
+                    Path::new(token).join(relative_path).display().to_string()
+                }
+            })
+            .unwrap_or_else(|| self.as_ref().display().to_string())
+    }
+
+    fn expand_tilde(&self) -> PathBuf {
+        match self.as_ref().strip_prefix("~") {
+            Ok(relative_path) if relative_path.as_os_str().is_empty() => home_dir().clone(),
+            Ok(relative_path) => home_dir().join(relative_path),
+            Err(_) => self.as_ref().to_path_buf(),
+        }
+    }
+
+    fn normalize(&self) -> PathBuf {
+        use std::path::Component;
+
+        let path = self.as_ref();
+        let mut normalized = PathBuf::new();
+        let mut normal_components = 0;
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => normalized.push(component),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if normal_components > 0 {
+                        normalized.pop();
+                        normal_components -= 1;
+                    } else if !path.has_root() {
+                        normalized.push(component);
+                    }
+                }
+                Component::Normal(name) => {
+                    normalized.push(name);
+                    normal_components += 1;
+                }
+            }
         }
+        normalized
+    }
+
+    fn relativize(&self, base: &Path) -> Option<PathBuf> {
+        SanitizedPath::new(self.as_ref())
+            .strip_prefix(SanitizedPath::new(base))
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    fn is_descendant_of(&self, ancestor: &Path) -> bool {
+        SanitizedPath::new(self.as_ref())
+            .strip_prefix(SanitizedPath::new(ancestor))
+            .is_ok_and(|relative_path| relative_path.components().next().is_some())
+    }
+
+    fn is_ancestor_of(&self, descendant: &Path) -> bool {
+        descendant.is_descendant_of(self.as_ref())
+    }
+
+    fn relative_to_marker(&self, marker: &str) -> Option<(PathBuf, PathBuf)> {
+        let path = self.as_ref();
+        let root = path
+            .ancestors()
+            .find(|ancestor| ancestor.join(marker).exists())?;
+        let relative_path = path.strip_prefix(root).ok()?;
+        Some((root.to_path_buf(), relative_path.to_path_buf()))
     }
 
     fn extension_or_hidden_file_name(&self) -> Option<&str> {
@@ -130,6 +307,18 @@ impl<T: AsRef<Path>> PathExt for T {
             .or_else(|| path.file_stem()?.to_str())
     }
 
+    fn compound_extension(&self, known_compound_extensions: &[&str]) -> Option<&str> {
+        let file_name = self.as_ref().file_name()?.to_str()?;
+        known_compound_extensions
+            .iter()
+            .filter_map(|extension| {
+                let stem = file_name.strip_suffix(extension)?.strip_suffix('.')?;
+                (!stem.is_empty()).then(|| &file_name[stem.len() + 1..])
+            })
+            .max_by_key(|extension| extension.len())
+            .or_else(|| self.extension_or_hidden_file_name())
+    }
+
     fn local_to_wsl(&self) -> Option<PathBuf> {
         // quite sketchy to convert this back to path at the end, but a lot of functions only accept paths
         // todo: ideally rework them..?
@@ -244,6 +433,14 @@ impl SanitizedPath {
         return Self::unchecked_new(dunce::simplified(path.as_ref()));
     }
 
+    /// Like [`SanitizedPath::new`], but also returns whether sanitizing changed the path,
+    /// such as by removing a UNC prefix. This is always `false` outside of Windows.
+    pub fn from_checked<T: AsRef<Path> + ?Sized>(path: &T) -> (&Self, bool) {
+        let sanitized = Self::new(path);
+        let changed = sanitized.as_path().as_os_str() != path.as_ref().as_os_str();
+        (sanitized, changed)
+    }
+
     pub fn unchecked_new<T: AsRef<Path> + ?Sized>(path: &T) -> &Self {
         // safe because `Path` and `SanitizedPath` have the same repr and Drop impl
         unsafe { mem::transmute::<&Path, &Self>(path.as_ref()) }
@@ -300,6 +497,15 @@ impl SanitizedPath {
         self.0.join(path)
     }
 
+    /// Like [`SanitizedPath::join`], but sanitizes the joined path, so it can be passed on
+    /// without wrapping it again.
+    ///
+    /// As with [`Path::join`], an absolute `path` replaces this one rather than being
+    /// appended to it, and is sanitized in turn.
+    pub fn join_raw<P: AsRef<Path>>(&self, path: P) -> Arc<Self> {
+        Self::new_arc(&self.0.join(path))
+    }
+
     pub fn parent(&self) -> Option<&Self> {
         self.0.parent().map(Self::unchecked_new)
     }
@@ -337,6 +543,14 @@ impl From<&SanitizedPath> for Arc<SanitizedPath> {
     }
 }
 
+impl From<&SanitizedPath> for Box<SanitizedPath> {
+    fn from(sanitized_path: &SanitizedPath) -> Self {
+        let path: Box<Path> = sanitized_path.0.into();
+        // safe because `Path` and `SanitizedPath` have the same repr and Drop impl
+        unsafe { mem::transmute(path) }
+    }
+}
+
 impl From<&SanitizedPath> for PathBuf {
     fn from(sanitized_path: &SanitizedPath) -> Self {
         sanitized_path.as_path().into()
@@ -349,6 +563,21 @@ impl AsRef<Path> for SanitizedPath {
     }
 }
 
+impl Serialize for SanitizedPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializing goes through [`SanitizedPath::new`], so paths read from disk are sanitized
+/// exactly once on load. `Arc<SanitizedPath>` deserializes through this impl as well.
+impl<'de> Deserialize<'de> for Box<SanitizedPath> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = PathBuf::deserialize(deserializer)?;
+        Ok(SanitizedPath::new(&path).into())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PathStyle {
     Posix,
@@ -405,6 +634,14 @@ impl PathStyle {
         *self == PathStyle::Windows
     }
 
+    /// Renders a local path with this style's separator, e.g. to display it for a remote host
+    /// whose platform differs from the local one. Only characters that are separators on the
+    /// local platform are converted, so a `\` in a POSIX file name is kept as is.
+    pub fn render(&self, path: &Path) -> String {
+        path.to_string_lossy()
+            .replace(PathStyle::local().separators_ch(), self.primary_separator())
+    }
+
     pub fn is_posix(&self) -> bool {
         *self == PathStyle::Posix
     }
@@ -661,6 +898,41 @@ pub fn normalize_lexically(path: &Path) -> Result<PathBuf, NormalizeError> {
     Ok(lexical)
 }
 
+/// Returns whether two paths name the same location, ignoring trailing separators, `.` and
+/// `..` components, and on Windows the `\\?\` prefix and the choice of separator.
+///
+/// Both paths are compared after [`PathExt::normalize`], so this never touches the
+/// filesystem and `a/b/../c` equals `a/c` even when `b` is a symbolic link.
+pub fn paths_equal(a: &Path, b: &Path) -> bool {
+    let normalized = |path| SanitizedPath::new(path).as_path().normalize();
+    normalized(a) == normalized(b)
+}
+
+/// Returns whether a path is absolute once it has been sanitized, so that on Windows paths
+/// with the `\\?\` prefix answer the same as their plain form.
+///
+/// Drive-rooted paths like `C:\x` and UNC paths like `\\server\share` are absolute. A
+/// Windows path rooted without a drive, like `\x`, is not, since it names a different file
+/// depending on the current drive.
+pub fn is_absolute_sanitized(path: &Path) -> bool {
+    SanitizedPath::new(path).as_path().is_absolute()
+}
+
+/// Resolves a path to an absolute one, following symbolic links when the path exists.
+///
+/// A path that can't be canonicalized, such as one that doesn't exist yet, is made absolute
+/// against the current directory and [normalized](PathExt::normalize) instead, without
+/// resolving any symbolic links along it. On Windows the `\\?\` prefix that
+/// canonicalization adds is removed.
+pub fn canonicalize_best_effort(path: &Path) -> PathBuf {
+    let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| {
+        std::path::absolute(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .normalize()
+    });
+    SanitizedPath::new(&resolved).as_path().to_path_buf()
+}
+
 /// Insert `path` into a set of "subtree" grants, keeping the set minimal.
 ///
 /// A subtree grant covers a path and all of its descendants. Insertion is a
@@ -684,9 +956,147 @@ pub fn path_within_subtree<'a>(path: &Path, mut subtrees: impl Iterator<Item = &
     subtrees.any(|granted| path.starts_with(granted))
 }
 
+/// Returns the longest path that all of `paths` start with, comparing them component-wise.
+///
+/// A single path is its own common ancestor. Returns `None` for an empty set, or when the
+/// paths share no components at all (e.g. relative paths with different first components,
+/// or paths on different Windows drives). Paths are sanitized first, so UNC prefixes on
+/// Windows don't prevent a match.
+pub fn common_ancestor<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Option<PathBuf> {
+    let mut paths = paths
+        .into_iter()
+        .map(|path| SanitizedPath::new(path).as_path());
+    let mut ancestor = paths.next()?.to_path_buf();
+    for path in paths {
+        ancestor = ancestor
+            .components()
+            .zip(path.components())
+            .take_while(|(ancestor_component, component)| ancestor_component == component)
+            .map(|(ancestor_component, _)| ancestor_component)
+            .collect();
+        if ancestor.as_os_str().is_empty() {
+            return None;
+        }
+    }
+    Some(ancestor)
+}
+
+/// Returns how many named components a path has, for indenting trees and sorting by depth,
+/// so `a/b/c` and `/a/b/c` both have a depth of 3.
+///
+/// Only normal components count: a root, drive prefix or `.` adds nothing, and neither
+/// does `..`, since the path isn't resolved first.
+pub fn path_depth(path: &Path) -> usize {
+    path.components()
+        .filter(|component| matches!(component, std::path::Component::Normal(_)))
+        .count()
+}
+
+/// Renders a path for display in at most `max_components` components by keeping the first
+/// and last components and replacing the middle with `…`, e.g. `a/b/c/d/e` with a budget of
+/// 3 becomes `a/…/e`.
+///
+/// The path is sanitized and [compacted](PathExt::compact) first, and a root or drive prefix
+/// stays attached to the first component. Components are never split, so budgets below 3 are
+/// treated as 3.
+pub fn truncate_path_middle(path: &Path, max_components: usize) -> String {
+    let path = SanitizedPath::new(path).as_path().compact();
+    let components = path.components().collect::<Vec<_>>();
+    let root_len = components
+        .iter()
+        .take_while(|component| {
+            matches!(
+                component,
+                std::path::Component::Prefix(_) | std::path::Component::RootDir
+            )
+        })
+        .count();
+    let names = &components[root_len..];
+    let max_components = max_components.max(3);
+    if names.len() <= max_components {
+        return path.to_string_lossy().into_owned();
+    }
+
+    let mut truncated = components[..=root_len].iter().collect::<PathBuf>();
+    truncated.push("…");
+    truncated.extend(&names[names.len() - (max_components - 2)..]);
+    truncated.to_string_lossy().into_owned()
+}
+
+/// Parses the paths out of a git-style diff header line, `diff --git a/old b/new`,
+/// `--- a/old` or `+++ b/new`, returning the old and new paths with their `a/` and `b/`
+/// prefixes [stripped](strip_diff_prefix).
+///
+/// A `---` or `+++` line names only one side, so the other is `None`, as is a side that is
+/// `/dev/null` because the file was added or deleted. Returns `None` for lines that aren't a
+/// diff header or that name no path at all.
+pub fn parse_diff_header(line: &str) -> Option<(Option<PathBuf>, Option<PathBuf>)> {
+    let line = line.trim_end();
+    let (old_path, new_path) = if let Some(paths) = line.strip_prefix("diff --git ") {
+        // Paths may contain spaces, so prefer the split where both sides name the same file,
+        // as they do for anything but a rename.
+        let splits = paths
+            .match_indices(' ')
+            .map(|(ix, _)| (&paths[..ix], &paths[ix + 1..]));
+        let (old_path, new_path) = splits
+            .clone()
+            .find(|(old_path, new_path)| {
+                strip_diff_prefix(Path::new(old_path)) == strip_diff_prefix(Path::new(new_path))
+            })
+            .or_else(|| {
+                splits
+                    .clone()
+                    .find(|(_, new_path)| new_path.starts_with("b/"))
+            })
+            .or_else(|| paths.split_once(' '))?;
+        (diff_header_path(old_path), diff_header_path(new_path))
+    } else if let Some(path) = line.strip_prefix("--- ") {
+        (diff_header_path(path), None)
+    } else if let Some(path) = line.strip_prefix("+++ ") {
+        (None, diff_header_path(path))
+    } else {
+        return None;
+    };
+    (old_path.is_some() || new_path.is_some()).then_some((old_path, new_path))
+}
+
+/// Reads one side of a diff header, dropping the timestamp that `diff -u` appends after a
+/// tab.
+fn diff_header_path(path: &str) -> Option<PathBuf> {
+    let path = path.split('\t').next()?.trim();
+    if path.is_empty() || path == "/dev/null" {
+        return None;
+    }
+    Some(strip_diff_prefix(Path::new(path)).to_path_buf())
+}
+
+/// Removes the leading `a/` or `b/` component that git adds to the old and new paths in a
+/// diff. Other paths, including a bare `a` or `b`, are returned unchanged.
+pub fn strip_diff_prefix(path: &Path) -> &Path {
+    let mut components = path.components();
+    match components.next() {
+        Some(std::path::Component::Normal(prefix))
+            if (prefix == "a" || prefix == "b") && !components.as_path().as_os_str().is_empty() =>
+        {
+            components.as_path()
+        }
+        _ => path,
+    }
+}
+
 /// A delimiter to use in `path_query:row_number:column_number` strings parsing.
 pub const FILE_ROW_COLUMN_DELIMITER: char = ':';
 
+/// Parses a row or column number, saturating at `u32::MAX` so that an overflowing number is
+/// still recognized as a position rather than left in the path.
+fn parse_position_number(number: &str) -> Option<u32> {
+    match number.parse::<u32>() {
+        Ok(number) => Some(number),
+        Err(error) if *error.kind() == std::num::IntErrorKind::PosOverflow => Some(u32::MAX),
+        Err(_) => None,
+    }
+}
+
 const ROW_COL_CAPTURE_REGEX: &str = r"(?xs)
     ([^\(]+)\:(?:
         \((\d+)[,:](\d+)\) # filename:(row,column), filename:(row:column)
@@ -709,14 +1119,25 @@ const ROW_COL_CAPTURE_REGEX: &str = r"(?xs)
         \:+()()$
     )";
 
-/// A representation of a path-like string with optional row and column numbers.
-/// Matching values example: `te`, `test.rs:22`, `te:22:5`, `test.c(22)`, `test.c(22,5)`etc.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+/// A representation of a path-like string with optional row and column numbers, or a byte offset.
+/// Matching values example: `te`, `test.rs:22`, `te:22:5`, `test.c(22)`, `test.c(22,5)`, `test.rs@1234` etc.
+///
+/// Values are ordered by path, compared component by component as [`Path`] does rather than
+/// with natural sorting, then by row and then column, with a missing row or column first.
+/// The offset and context only break ties.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub struct PathWithPosition {
     pub path: PathBuf,
     pub row: Option<u32>,
     // Absent if row is absent.
     pub column: Option<u32>,
+    /// A byte offset into the file, as emitted by tools that report `file.rs@1234`.
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Text following the position in grep-style output, such as the message in
+    /// `file.rs:10:5: unused variable`. Not emitted by [`PathWithPosition::to_string`].
+    #[serde(default)]
+    pub context: Option<String>,
 }
 
 impl PathWithPosition {
@@ -726,6 +1147,8 @@ impl PathWithPosition {
             path,
             row: None,
             column: None,
+            offset: None,
+            context: None,
         }
     }
 
@@ -734,6 +1157,12 @@ impl PathWithPosition {
     /// Ignores trailing `:`s, so `test.rs:22:` is parsed as `test.rs:22`.
     /// If the suffix parsing fails, the whole string is parsed as a path.
     ///
+    /// A byte offset is recognized only as an `@offset` suffix that ends the string, so row and
+    /// column suffixes take precedence: `test.rs@12:3` is parsed as row 3 of `test.rs@12`.
+    ///
+    /// Grep-style output such as `test.rs:10:5: message` keeps the text after the position,
+    /// which needs to be separated from it by whitespace, as the `context`.
+    ///
     /// Be mindful that `test_file:10:1:` is a valid posix filename.
     /// `PathWithPosition` class assumes that the ending position-like suffix is **not** part of the filename.
     ///
@@ -746,26 +1175,64 @@ impl PathWithPosition {
     ///     path: PathBuf::from("test_file"),
     ///     row: None,
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file:10"), PathWithPosition {
     ///     path: PathBuf::from("test_file"),
     ///     row: Some(10),
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: None,
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1:2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: Some(2),
+    ///     offset: None,
+    ///     context: None,
+    /// });
+    /// assert_eq!(PathWithPosition::parse_str("test_file.rs@1234"), PathWithPosition {
+    ///     path: PathBuf::from("test_file.rs"),
+    ///     row: None,
+    ///     column: None,
+    ///     offset: Some(1234),
+    ///     context: None,
+    /// });
+    /// assert_eq!(PathWithPosition::parse_str("test_file.rs@12:3"), PathWithPosition {
+    ///     path: PathBuf::from("test_file.rs@12"),
+    ///     row: Some(3),
+    ///     column: None,
+    ///     offset: None,
+    ///     context: None,
+    /// });
+    /// assert_eq!(PathWithPosition::parse_str("file.rs:10:5: unused variable"), PathWithPosition {
+    ///     path: PathBuf::from("file.rs"),
+    ///     row: Some(10),
+    ///     column: Some(5),
+    ///     offset: None,
+    ///     context: Some("unused variable".to_string()),
+    /// });
+    /// assert_eq!(PathWithPosition::parse_str("file.rs:10: error: expected `;`"), PathWithPosition {
+    ///     path: PathBuf::from("file.rs"),
+    ///     row: Some(10),
+    ///     column: None,
+    ///     offset: None,
+    ///     context: Some("error: expected `;`".to_string()),
     /// });
     /// ```
     ///
@@ -777,44 +1244,71 @@ impl PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:a"),
     ///     row: None,
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:a:b"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:a:b"),
     ///     row: None,
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: None,
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs::1"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1::"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs::1:2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: Some(2),
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1::2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:1"),
     ///     row: Some(2),
     ///     column: None,
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1:2:3"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:1"),
     ///     row: Some(2),
     ///     column: Some(3),
+    ///     offset: None,
+    ///     context: None,
     /// });
     /// ```
     pub fn parse_str(s: &str) -> Self {
+        static CONTEXT_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^(.+?:\d+(?::\d+)?):\s+(\S.*)$").unwrap());
+        if let Some((_, [position, context])) =
+            CONTEXT_RE.captures(s.trim()).map(|caps| caps.extract())
+        {
+            return Self {
+                context: Some(context.to_string()),
+                ..Self::parse_str(position)
+            };
+        }
+
         let trimmed = s.trim();
         let path = Path::new(trimmed);
         let Some(maybe_file_name_with_row_col) = path.file_name().unwrap_or_default().to_str()
@@ -823,6 +1317,8 @@ impl PathWithPosition {
                 path: Path::new(s).to_path_buf(),
                 row: None,
                 column: None,
+                offset: None,
+                context: None,
             };
         };
         if maybe_file_name_with_row_col.is_empty() {
@@ -830,6 +1326,22 @@ impl PathWithPosition {
                 path: Path::new(s).to_path_buf(),
                 row: None,
                 column: None,
+                offset: None,
+                context: None,
+            };
+        }
+
+        if let Some((file_name, maybe_offset)) = maybe_file_name_with_row_col.rsplit_once('@')
+            && !file_name.is_empty()
+            && maybe_offset.bytes().all(|byte| byte.is_ascii_digit())
+            && let Ok(offset) = maybe_offset.parse::<usize>()
+        {
+            return Self {
+                path: Path::new(&trimmed[..trimmed.len() - maybe_offset.len() - 1]).to_path_buf(),
+                row: None,
+                column: None,
+                offset: Some(offset),
+                context: None,
             };
         }
 
@@ -843,8 +1355,8 @@ impl PathWithPosition {
             .map(|caps| caps.extract())
         {
             Some((_, [file_name, maybe_row, maybe_column])) => {
-                let row = maybe_row.parse::<u32>().ok();
-                let column = maybe_column.parse::<u32>().ok();
+                let row = parse_position_number(maybe_row);
+                let column = parse_position_number(maybe_column);
 
                 let (_, suffix) = trimmed.split_once(file_name).unwrap();
                 let path_without_suffix = &trimmed[..trimmed.len() - suffix.len()];
@@ -853,48 +1365,207 @@ impl PathWithPosition {
                     path: Path::new(path_without_suffix).to_path_buf(),
                     row,
                     column,
+                    offset: None,
+                    context: None,
                 }
             }
             None => {
                 // The `ROW_COL_CAPTURE_REGEX` deals with separated digits only,
                 // but in reality there could be `foo/bar.py:22:in` inputs which we want to match too.
                 // The regex mentioned is not very extendable with "digit or random string" checks, so do this here instead.
-                let delimiter = ':';
-                let mut path_parts = s
-                    .rsplitn(3, delimiter)
-                    .collect::<Vec<_>>()
-                    .into_iter()
-                    .rev()
-                    .fuse();
-                let mut path_string = path_parts.next().expect("rsplitn should have the rest of the string as its last parameter that we reversed").to_owned();
-                let mut row = None;
-                let mut column = None;
-                if let Some(maybe_row) = path_parts.next() {
-                    if let Ok(parsed_row) = maybe_row.parse::<u32>() {
-                        row = Some(parsed_row);
-                        if let Some(parsed_column) = path_parts
-                            .next()
-                            .and_then(|maybe_col| maybe_col.parse::<u32>().ok())
-                        {
-                            column = Some(parsed_column);
-                        }
-                    } else {
-                        path_string.push(delimiter);
-                        path_string.push_str(maybe_row);
-                    }
-                }
-                for split in path_parts {
-                    path_string.push(delimiter);
-                    path_string.push_str(split);
-                }
+                Self::split_row_column(s, FILE_ROW_COLUMN_DELIMITER)
+            }
+        }
+    }
 
-                Self {
-                    path: PathBuf::from(path_string),
-                    row,
-                    column,
+    /// Like [`PathWithPosition::parse_str`], but for tools that separate the path, row and
+    /// column with another character, such as `file.rs#10#5`.
+    ///
+    /// Only the `path<delimiter>row<delimiter>column` and `path<delimiter>row` forms are
+    /// recognized for other delimiters. The colon delimiter parses exactly like `parse_str`.
+    pub fn parse_str_with_delimiter(s: &str, delimiter: char) -> Self {
+        if delimiter == FILE_ROW_COLUMN_DELIMITER {
+            Self::parse_str(s)
+        } else {
+            Self::split_row_column(s.trim(), delimiter)
+        }
+    }
+
+    fn split_row_column(s: &str, delimiter: char) -> Self {
+        // The colon after a Windows drive letter belongs to the path, even when nothing
+        // separates it from a digit, as in the drive-relative `C:10`.
+        let (drive, s) = match s.as_bytes() {
+            [letter, b':', ..]
+                if cfg!(windows)
+                    && delimiter == FILE_ROW_COLUMN_DELIMITER
+                    && letter.is_ascii_alphabetic() =>
+            {
+                s.split_at(2)
+            }
+            _ => ("", s),
+        };
+        let mut path_parts = s
+            .rsplitn(3, delimiter)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .fuse();
+        let mut path_string = drive.to_owned();
+        path_string.push_str(path_parts.next().expect(
+            "rsplitn should have the rest of the string as its last parameter that we reversed",
+        ));
+        let mut row = None;
+        let mut column = None;
+        if let Some(maybe_row) = path_parts.next() {
+            if let Some(parsed_row) = parse_position_number(maybe_row) {
+                row = Some(parsed_row);
+                if let Some(parsed_column) = path_parts.next().and_then(parse_position_number) {
+                    column = Some(parsed_column);
                 }
+            } else {
+                path_string.push(delimiter);
+                path_string.push_str(maybe_row);
             }
         }
+        for split in path_parts {
+            path_string.push(delimiter);
+            path_string.push_str(split);
+        }
+
+        Self {
+            path: PathBuf::from(path_string),
+            row,
+            column,
+            offset: None,
+            context: None,
+        }
+    }
+
+    /// Finds every `path:row:column`, `path:row` or `path(row,column)` reference in a line of
+    /// text, such as terminal output or a commit message, returning each with its byte range
+    /// in `text`.
+    ///
+    /// References end at whitespace, quotes and brackets, so several can share a line, and
+    /// each one is parsed with [`PathWithPosition::parse_str`]. URLs such as
+    /// `http://localhost:8080` are skipped rather than read as a path and a row.
+    ///
+    /// ```
+    /// # use util::paths::PathWithPosition;
+    /// let references = PathWithPosition::parse_all("moved src/a.rs:3:1 to src/b.rs:10");
+    /// assert_eq!(references[0].0, 6..18);
+    /// assert_eq!(references[1].1.row, Some(10));
+    /// ```
+    pub fn parse_all(text: &str) -> Vec<(Range<usize>, Self)> {
+        static REFERENCE_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r#"[^\s'"`<>\[\]()]+(?::\d+(?::\d+)?|\(\d+(?:[,:]\d+)?\))"#).unwrap()
+        });
+        REFERENCE_RE
+            .find_iter(text)
+            .filter(|reference| !reference.as_str().contains("://"))
+            .map(|reference| (reference.range(), Self::parse_str(reference.as_str())))
+            .filter(|(_, path_with_position)| path_with_position.row.is_some())
+            .collect()
+    }
+
+    /// Parses a `file://` URI, such as the ones handed over by language servers and browsers.
+    ///
+    /// The path is percent-decoded, and a position is taken from a `#L<row>` or
+    /// `#L<row>C<column>` fragment, or otherwise from a `:row:column` or `@offset` suffix as in
+    /// [`PathWithPosition::parse_str`]. The suffix is only recognized while the URI is still
+    /// encoded, so an escaped `%3A` stays part of the file name. URIs with any other scheme are
+    /// rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use util::paths::{PathStyle, PathWithPosition};
+    /// # use std::path::PathBuf;
+    /// assert_eq!(
+    ///     PathWithPosition::parse_uri("file:///home/me/a.rs#L10", PathStyle::Posix).unwrap(),
+    ///     PathWithPosition {
+    ///         path: PathBuf::from("/home/me/a.rs"),
+    ///         row: Some(10),
+    ///         column: None,
+    ///         offset: None,
+    ///         context: None,
+    ///     }
+    /// );
+    /// assert_eq!(
+    ///     PathWithPosition::parse_uri("file:///home/me/my%20file.rs#L3C7", PathStyle::Posix)
+    ///         .unwrap(),
+    ///     PathWithPosition {
+    ///         path: PathBuf::from("/home/me/my file.rs"),
+    ///         row: Some(3),
+    ///         column: Some(7),
+    ///         offset: None,
+    ///         context: None,
+    ///     }
+    /// );
+    /// assert!(PathWithPosition::parse_uri("https://zed.dev/a.rs#L1", PathStyle::Posix).is_err());
+    /// ```
+    pub fn parse_uri(uri: &str, path_style: PathStyle) -> anyhow::Result<Self> {
+        static FRAGMENT_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^L(\d+)(?:C(\d+))?$").unwrap());
+        static SUFFIX_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"[^/](:(\d+)(?::(\d+))?|@(\d+))$").unwrap());
+
+        let mut url = url::Url::parse(uri.trim())?;
+        anyhow::ensure!(
+            url.scheme() == "file",
+            "unsupported URI scheme {:?}, expected \"file\"",
+            url.scheme()
+        );
+        let fragment_position = url
+            .fragment()
+            .and_then(|fragment| FRAGMENT_RE.captures(fragment))
+            .map(|captures| {
+                (
+                    parse_position_number(&captures[1]),
+                    captures
+                        .get(2)
+                        .and_then(|column| parse_position_number(column.as_str())),
+                )
+            });
+        url.set_fragment(None);
+
+        let mut suffix_position = None;
+        if fragment_position.is_none()
+            && let Some(captures) = SUFFIX_RE.captures(url.path())
+            && let Some(suffix) = captures.get(1)
+        {
+            suffix_position = Some((
+                captures
+                    .get(2)
+                    .and_then(|row| parse_position_number(row.as_str())),
+                captures
+                    .get(3)
+                    .and_then(|column| parse_position_number(column.as_str())),
+                captures
+                    .get(4)
+                    .and_then(|offset| offset.as_str().parse::<usize>().ok()),
+            ));
+            let path = url.path()[..suffix.start()].to_string();
+            url.set_path(&path);
+        }
+
+        let path = url
+            .to_file_path_ext(path_style)
+            .map_err(|()| anyhow::anyhow!("invalid file URI: {uri}"))?;
+
+        Ok(match (fragment_position, suffix_position) {
+            (Some((row, column)), _) => Self {
+                row,
+                column: row.and(column),
+                ..Self::from_path(path)
+            },
+            (None, Some((row, column, offset))) => Self {
+                row,
+                column: row.and(column),
+                offset,
+                ..Self::from_path(path)
+            },
+            (None, None) => Self::from_path(path),
+        })
     }
 
     pub fn map_path<E>(
@@ -905,28 +1576,58 @@ impl PathWithPosition {
             path: mapping(self.path)?,
             row: self.row,
             column: self.column,
+            offset: self.offset,
+            context: self.context,
         })
     }
 
     pub fn to_string(&self, path_to_string: &dyn Fn(&PathBuf) -> String) -> String {
+        self.to_string_with_delimiter(path_to_string, FILE_ROW_COLUMN_DELIMITER)
+    }
+
+    /// Like [`PathWithPosition::to_string`], but separates the row and column with
+    /// `delimiter`, the inverse of [`PathWithPosition::parse_str_with_delimiter`].
+    pub fn to_string_with_delimiter(
+        &self,
+        path_to_string: &dyn Fn(&PathBuf) -> String,
+        delimiter: char,
+    ) -> String {
         let path_string = path_to_string(&self.path);
-        if let Some(row) = self.row {
-            if let Some(column) = self.column {
-                format!("{path_string}:{row}:{column}")
-            } else {
-                format!("{path_string}:{row}")
-            }
-        } else {
-            path_string
+        match self.location_suffix_with_delimiter(delimiter) {
+            Some(suffix) => path_string + &suffix,
+            None => path_string,
+        }
+    }
+
+    /// Returns the final component of the path, for showing apart from its location.
+    pub fn file_name(&self) -> Option<&OsStr> {
+        self.path.file_name()
+    }
+
+    /// Returns what [`PathWithPosition::to_string`] appends to the path: `:10:5` or `:10`
+    /// for a row and column or a row alone, `@1234` for a byte offset without a row, and
+    /// `None` when there's no position.
+    pub fn location_suffix(&self) -> Option<String> {
+        self.location_suffix_with_delimiter(FILE_ROW_COLUMN_DELIMITER)
+    }
+
+    fn location_suffix_with_delimiter(&self, delimiter: char) -> Option<String> {
+        match (self.row, self.column, self.offset) {
+            (Some(row), Some(column), _) => Some(format!("{delimiter}{row}{delimiter}{column}")),
+            (Some(row), None, _) => Some(format!("{delimiter}{row}")),
+            (None, _, Some(offset)) => Some(format!("@{offset}")),
+            (None, _, None) => None,
         }
     }
 }
 
 #[derive(Clone)]
 pub struct PathMatcher {
-    sources: Vec<(String, RelPathBuf, /*trailing separator*/ bool)>,
+    /// Every glob, with its literal path when it's a valid relative path.
+    sources: Vec<(String, Option<RelPathBuf>, /*trailing separator*/ bool)>,
     glob: GlobSet,
     path_style: PathStyle,
+    anchored: bool,
 }
 
 impl std::fmt::Debug for PathMatcher {
@@ -934,87 +1635,357 @@ impl std::fmt::Debug for PathMatcher {
         f.debug_struct("PathMatcher")
             .field("sources", &self.sources)
             .field("path_style", &self.path_style)
+            .field("anchored", &self.anchored)
             .finish()
     }
 }
 
+/// Matchers are equal when they match the same paths, so the order of their globs and any
+/// repeated globs are ignored.
 impl PartialEq for PathMatcher {
     fn eq(&self, other: &Self) -> bool {
-        self.sources.eq(&other.sources)
+        self.anchored == other.anchored && self.normalized_sources() == other.normalized_sources()
     }
 }
 
 impl Eq for PathMatcher {}
 
 impl PathMatcher {
+    /// Builds a matcher from any iterator of globs, borrowing each one while it is parsed,
+    /// so callers don't need to collect them into a `Vec<String>` first.
+    ///
+    /// Globs may use brace alternation, such as `*.{rs,toml}`. If any glob fails to parse,
+    /// the returned error names it through [`globset::Error::glob`].
     pub fn new(
         globs: impl IntoIterator<Item = impl AsRef<str>>,
         path_style: PathStyle,
     ) -> Result<Self, globset::Error> {
-        let globs = globs
-            .into_iter()
-            .map(|as_str| {
-                GlobBuilder::new(as_str.as_ref())
-                    .backslash_escape(path_style.is_posix())
-                    .build()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let sources = globs
-            .iter()
-            .filter_map(|glob| {
-                let glob = glob.glob();
-                Some((
-                    glob.to_string(),
-                    RelPath::new(&glob.as_ref(), path_style)
-                        .ok()
-                        .map(std::borrow::Cow::into_owned)?,
-                    glob.ends_with(path_style.separators_ch()),
-                ))
-            })
-            .collect();
-        let mut glob_builder = GlobSetBuilder::new();
-        for single_glob in globs {
-            glob_builder.add(single_glob);
-        }
-        let glob = glob_builder.build()?;
-        Ok(PathMatcher {
-            glob,
-            sources,
-            path_style,
-        })
+        PathMatcherBuilder::new(path_style).add_all(globs)?.build()
     }
 
+    /// Makes every glob match from the root of the relative path only.
+    ///
+    /// By default a literal glob such as `target` also matches wherever the path ends with it,
+    /// like `crates/foo/target`, and a path is retried with a trailing separator so that it
+    /// matches directory globs like `target/`. An anchored matcher drops both, so a path
+    /// matches only when a glob matches it whole or it lies inside a literal glob's path, as
+    /// `target/debug` does for `target`. Globs starting with `**/` still match at any depth,
+    /// since the glob itself spans the leading directories.
+    pub fn anchored(mut self, anchored: bool) -> Self {
+        self.anchored = anchored;
+        self
+    }
+
+    /// The globs that are valid relative paths. Others, such as absolute globs, still take
+    /// part in matching but aren't listed, since callers treat these as the effective filters
+    /// for worktree-relative paths.
     pub fn sources(&self) -> impl Iterator<Item = &str> + Clone {
+        self.sources
+            .iter()
+            .filter(|(_, literal, _)| literal.is_some())
+            .map(|(source, ..)| source.as_str())
+    }
+
+    /// Every glob the matcher was built from, including those left out of
+    /// [`PathMatcher::sources`].
+    fn globs(&self) -> impl Iterator<Item = &str> {
         self.sources.iter().map(|(source, ..)| source.as_str())
     }
 
+    /// The globs sorted and deduplicated, which is all that decides what a matcher matches.
+    fn normalized_sources(&self) -> Vec<&str> {
+        let mut sources = self.globs().collect::<Vec<_>>();
+        sources.sort_unstable();
+        sources.dedup();
+        sources
+    }
+
+    /// Returns whether no globs were given, in which case nothing matches.
+    pub fn is_empty(&self) -> bool {
+        self.glob.is_empty()
+    }
+
+    /// Returns the number of globs this matcher was built from.
+    pub fn len(&self) -> usize {
+        self.glob.len()
+    }
+
     pub fn is_match<P: AsRef<RelPath>>(&self, other: P) -> bool {
+        if self.is_empty() {
+            return false;
+        }
         let other = other.as_ref();
+        let other_path = other.display(self.path_style);
+
+        // The glob set matches every source in a single pass, so try it before
+        // checking the sources one at a time as literal prefixes and suffixes.
+        if self.glob.is_match(&*other_path) {
+            return true;
+        }
+
         if self
             .sources
             .iter()
-            .any(|(_, source, _)| other.starts_with(source) || other.ends_with(source))
+            .filter_map(|(_, source, _)| source.as_ref())
+            .any(|source| other.starts_with(source) || (!self.anchored && other.ends_with(source)))
         {
             return true;
         }
-        let other_path = other.display(self.path_style);
-
-        if self.glob.is_match(&*other_path) {
-            return true;
-        }
 
-        self.glob
-            .is_match(other_path.into_owned() + self.path_style.primary_separator())
+        !self.anchored
+            && self
+                .glob
+                .is_match(other_path.into_owned() + self.path_style.primary_separator())
     }
 
-    pub fn is_match_std_path<P: AsRef<Path>>(&self, other: P) -> bool {
-        let other = other.as_ref();
-        if self.sources.iter().any(|(_, source, _)| {
-            other.starts_with(source.as_std_path()) || other.ends_with(source.as_std_path())
-        }) {
-            return true;
+    /// Explains [`PathMatcher::is_match`] for a path: which glob matched it and how, for
+    /// diagnostics such as "why is this file excluded?".
+    ///
+    /// The checks run in the same order as in `is_match`, so the explanation names the first
+    /// glob and reason that `is_match` would have stopped at.
+    pub fn explain<P: AsRef<RelPath>>(&self, path: P) -> MatchExplanation {
+        let path = path.as_ref();
+        let display_path = path.display(self.path_style);
+        let matched = |glob_index: usize, reason| MatchExplanation::Matched {
+            glob_index,
+            source: self.sources[glob_index].0.clone(),
+            reason,
+        };
+
+        if let Some(&glob_index) = self.glob.matches(&*display_path).first() {
+            return matched(glob_index, MatchReason::Glob);
+        }
+
+        for (glob_index, (_, literal, _)) in self.sources.iter().enumerate() {
+            let Some(literal) = literal else {
+                continue;
+            };
+            if path.starts_with(literal) {
+                return matched(glob_index, MatchReason::LiteralPrefix);
+            }
+            if !self.anchored && path.ends_with(literal) {
+                return matched(glob_index, MatchReason::LiteralSuffix);
+            }
+        }
+
+        if !self.anchored {
+            let path_with_separator =
+                display_path.into_owned() + self.path_style.primary_separator();
+            if let Some(&glob_index) = self.glob.matches(path_with_separator).first() {
+                return matched(glob_index, MatchReason::TrailingSeparator);
+            }
+        }
+
+        MatchExplanation::NoMatch
+    }
+
+    /// Returns whether any single component of the path is exactly one of this matcher's
+    /// globs, so that `node_modules` matches `web/node_modules/react` without a `**/` around it.
+    ///
+    /// Only globs that are a plain name take part; ones with a separator or glob syntax
+    /// are skipped. This is a simpler check than [`PathMatcher::is_match`], meant for
+    /// ignoring directories by name wherever they appear.
+    pub fn match_any_component<P: AsRef<RelPath>>(&self, path: P) -> bool {
+        let mut names = self.sources().filter(|source| {
+            !source.is_empty()
+                && !source.contains(['*', '?', '[', '{'])
+                && !source.contains(self.path_style.separators_ch())
+        });
+        let path = path.as_ref();
+        names.any(|name| path.components().any(|component| component == name))
+    }
+
+    /// Equivalent to [`PathMatcher::is_match`] for a relative path rendered in this matcher's
+    /// path style, but matches the string in place instead of building a [`RelPath`], and
+    /// reuses a buffer for the trailing separator check.
+    pub fn is_match_str(&self, path: &str) -> bool {
+        thread_local! {
+            static PATH_WITH_SEPARATOR: std::cell::RefCell<String> =
+                const { std::cell::RefCell::new(String::new()) };
+        }
+
+        if self.is_empty() {
+            return false;
+        }
+
+        if self.glob.is_match(path) {
+            return true;
+        }
+
+        let path_components = || {
+            path.split(self.path_style.separators_ch())
+                .filter(|component| !component.is_empty())
+        };
+        if self
+            .sources
+            .iter()
+            .filter_map(|(_, source, _)| source.as_ref())
+            .any(|source| {
+                let source_components = || {
+                    source
+                        .as_unix_str()
+                        .split('/')
+                        .filter(|component| !component.is_empty())
+                };
+                components_start_with(path_components(), source_components())
+                    || (!self.anchored
+                        && components_start_with(
+                            path_components().rev(),
+                            source_components().rev(),
+                        ))
+            })
+        {
+            return true;
         }
+
+        !self.anchored
+            && PATH_WITH_SEPARATOR.with_borrow_mut(|path_with_separator| {
+                path_with_separator.clear();
+                path_with_separator.push_str(path);
+                path_with_separator.push_str(self.path_style.primary_separator());
+                self.glob.is_match(path_with_separator.as_str())
+            })
+    }
+
+    pub fn is_match_std_path<P: AsRef<Path>>(&self, other: P) -> bool {
+        let other = other.as_ref();
         self.glob.is_match(other)
+            || self
+                .sources
+                .iter()
+                .filter_map(|(_, source, _)| source.as_ref())
+                .any(|source| {
+                    other.starts_with(source.as_std_path())
+                        || (!self.anchored && other.ends_with(source.as_std_path()))
+                })
+    }
+}
+
+fn components_start_with<'a>(
+    mut components: impl Iterator<Item = &'a str>,
+    mut prefix: impl Iterator<Item = &'a str>,
+) -> bool {
+    prefix.all(|prefix_component| components.next() == Some(prefix_component))
+}
+
+/// Why a [`PathMatcher`] did or didn't match a path, as returned by [`PathMatcher::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchExplanation {
+    NoMatch,
+    Matched {
+        /// The position of the glob among the matcher's [sources](PathMatcher::sources).
+        glob_index: usize,
+        source: String,
+        reason: MatchReason,
+    },
+}
+
+impl MatchExplanation {
+    pub fn is_match(&self) -> bool {
+        matches!(self, MatchExplanation::Matched { .. })
+    }
+}
+
+/// The check of [`PathMatcher::is_match`] that accepted a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchReason {
+    /// The glob matched the whole path.
+    Glob,
+    /// The path is the glob's literal path or lies inside it, as `target/debug` does for
+    /// `target`.
+    LiteralPrefix,
+    /// The path ends with the glob's literal path, as `crates/foo/target` does for `target`.
+    LiteralSuffix,
+    /// The glob only matched once a separator was appended, as `output` does for `out*/`.
+    TrailingSeparator,
+}
+
+/// Accumulates globs for a [`PathMatcher`], so that patterns discovered over time (e.g. from
+/// nested ignore files) are parsed once rather than every time the matcher is rebuilt.
+#[derive(Clone, Debug)]
+pub struct PathMatcherBuilder {
+    /// Every glob, with its literal path when it's a valid relative path.
+    sources: Vec<(String, Option<RelPathBuf>, /*trailing separator*/ bool)>,
+    glob_builder: GlobSetBuilder,
+    path_style: PathStyle,
+}
+
+impl PathMatcherBuilder {
+    pub fn new(path_style: PathStyle) -> Self {
+        Self {
+            sources: Vec::new(),
+            glob_builder: GlobSetBuilder::new(),
+            path_style,
+        }
+    }
+
+    /// Parses and adds a single glob. On error the builder is left unchanged, and the error
+    /// carries the offending glob.
+    pub fn add(&mut self, glob: &str) -> Result<&mut Self, globset::Error> {
+        let glob = GlobBuilder::new(glob)
+            .backslash_escape(self.path_style.is_posix())
+            .build()?;
+        let source = glob.glob();
+        self.sources.push((
+            source.to_string(),
+            RelPath::new(source.as_ref(), self.path_style)
+                .ok()
+                .map(Cow::into_owned),
+            source.ends_with(self.path_style.separators_ch()),
+        ));
+        self.glob_builder.add(glob);
+        Ok(self)
+    }
+
+    /// Adds every glob in order, stopping at the first one that fails to parse.
+    pub fn add_all(
+        &mut self,
+        globs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<&mut Self, globset::Error> {
+        for glob in globs {
+            self.add(glob.as_ref())?;
+        }
+        Ok(self)
+    }
+
+    /// Compiles the globs added so far. The builder can keep accepting globs afterwards.
+    pub fn build(&self) -> Result<PathMatcher, globset::Error> {
+        Ok(PathMatcher {
+            sources: self.sources.clone(),
+            glob: self.glob_builder.build()?,
+            path_style: self.path_style,
+            anchored: false,
+        })
+    }
+}
+
+/// Serializes as the list of globs the matcher was built from.
+impl Serialize for PathMatcher {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.globs())
+    }
+}
+
+/// Deserializes a list of globs for the local path style, failing on the first glob that
+/// doesn't parse.
+impl<'de> Deserialize<'de> for PathMatcher {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let globs = Vec::<String>::deserialize(deserializer)?;
+        PathMatcher::new(&globs, PathStyle::local()).map_err(serde::de::Error::custom)
+    }
+}
+
+impl schemars::JsonSchema for PathMatcher {
+    fn schema_name() -> Cow<'static, str> {
+        "PathMatcher".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "A list of glob patterns, such as `**/*.rs` or `target/**`."
+        })
     }
 }
 
@@ -1024,6 +1995,7 @@ impl Default for PathMatcher {
             path_style: PathStyle::local(),
             glob: GlobSet::empty(),
             sources: vec![],
+            anchored: false,
         }
     }
 }
@@ -1039,7 +2011,7 @@ impl Default for PathMatcher {
 /// The function implements the following comparison rules:
 /// 1. Different numeric values: Compares by actual numeric value (e.g., "2" < "10")
 /// 2. Leading zeros: When values are equal, longer sequence wins (e.g., "002" > "2")
-/// 3. Large numbers: Falls back to string comparison for numbers that would overflow u128
+/// 3. Large numbers: Digits are compared without parsing, so numbers of any length never overflow
 ///
 /// # Examples
 ///
@@ -1054,11 +2026,8 @@ impl Default for PathMatcher {
 /// # Implementation Details
 ///
 /// 1. Extracts consecutive digits into strings
-/// 2. Compares sequence lengths for leading zero handling
-/// 3. For equal lengths, compares digit by digit
-/// 4. For different lengths:
-///    - Attempts numeric comparison first (for numbers up to 2^128 - 1)
-///    - Falls back to string comparison if numbers would overflow
+/// 2. Compares values by their length without leading zeros, then digit by digit
+/// 3. For equal values, the longer sequence (more leading zeros) is greater
 ///
 /// The function advances both iterators past their respective numeric sequences,
 /// regardless of the comparison result.
@@ -1091,30 +2060,8 @@ where
         b_iter.next();
     }
 
-    // First compare lengths (handle leading zeros)
-    match a_num_str.len().cmp(&b_num_str.len()) {
-        Ordering::Equal => {
-            // Same length, compare digit by digit
-            match a_num_str.cmp(&b_num_str) {
-                Ordering::Equal => Ordering::Equal,
-                ordering => ordering,
-            }
-        }
-
-        // Different lengths but same value means leading zeros
-        ordering => {
-            // Try parsing as numbers first
-            if let (Ok(a_val), Ok(b_val)) = (a_num_str.parse::<u128>(), b_num_str.parse::<u128>()) {
-                match a_val.cmp(&b_val) {
-                    Ordering::Equal => ordering, // Same value, longer one is greater (leading zeros)
-                    ord => ord,
-                }
-            } else {
-                // If parsing fails (overflow), compare as strings
-                a_num_str.cmp(&b_num_str)
-            }
-        }
-    }
+    // Same value, longer one is greater (leading zeros)
+    compare_digit_runs(&a_num_str, &b_num_str).then_with(|| a_num_str.len().cmp(&b_num_str.len()))
 }
 
 /// Performs natural sorting comparison between two strings.
@@ -1127,7 +2074,7 @@ where
 /// * Case-sensitive with lowercase priority: When comparing same letters, lowercase comes before uppercase
 /// * Numbers are compared by numeric value, not character by character
 /// * Leading zeros affect ordering when numeric values are equal
-/// * Can handle numbers larger than u128::MAX (falls back to string comparison)
+/// * Can handle numbers of any length, including ones larger than u128::MAX
 /// * When strings are equal case-insensitively, lowercase is prioritized (lowercase < uppercase)
 ///
 /// # Algorithm
@@ -1139,6 +2086,45 @@ where
 /// 4. For non-numeric characters, using case-insensitive comparison
 /// 5. If everything is equal case-insensitively, using case-sensitive comparison as final tie-breaker
 pub fn natural_sort(a: &str, b: &str) -> Ordering {
+    natural_sort_with(a, b, |a_char, b_char| {
+        a_char
+            .to_ascii_lowercase()
+            .cmp(&b_char.to_ascii_lowercase())
+    })
+}
+
+/// Natural sort that folds case using Unicode rules rather than ASCII only.
+///
+/// Behaves like [`natural_sort`], but non-digit characters are compared by their full Unicode
+/// lowercase mapping, so `Ä` and `ä` compare equal until the original case is used as the final
+/// tie-breaker. Accents are not folded: `ä` still sorts after `z`.
+pub fn natural_sort_case_folded(a: &str, b: &str) -> Ordering {
+    natural_sort_with(a, b, |a_char, b_char| {
+        a_char.to_lowercase().cmp(b_char.to_lowercase())
+    })
+}
+
+/// Natural sort for whole relative paths compared as flat strings, such as search results,
+/// where path separators sort before every other character.
+///
+/// Behaves like [`natural_sort`] otherwise, but keeps the contents of `a/` together ahead of
+/// siblings like `a-b.txt` and `a.txt`, whose `-` and `.` would otherwise sort before `/`.
+/// Both `/` and the platform's separators count as separators.
+pub fn natural_sort_paths(a: &str, b: &str) -> Ordering {
+    let is_separator = |c: char| c == '/' || std::path::is_separator(c);
+    natural_sort_with(a, b, |a_char, b_char| {
+        match (is_separator(a_char), is_separator(b_char)) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a_char
+                .to_ascii_lowercase()
+                .cmp(&b_char.to_ascii_lowercase()),
+        }
+    })
+}
+
+fn natural_sort_with(a: &str, b: &str, compare_chars: impl Fn(char, char) -> Ordering) -> Ordering {
     let mut a_iter = a.chars().peekable();
     let mut b_iter = b.chars().peekable();
 
@@ -1155,14 +2141,172 @@ pub fn natural_sort(a: &str, b: &str) -> Ordering {
                         Ordering::Equal => continue,
                         ordering => return ordering,
                     }
+                } else {
+                    match compare_chars(a_char, b_char) {
+                        Ordering::Equal => {
+                            a_iter.next();
+                            b_iter.next();
+                        }
+                        ordering => return ordering,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Performs natural sorting comparison that understands decimal and negative numbers.
+///
+/// Unlike [`natural_sort`], which compares every run of digits as an integer, numbers here may
+/// carry a fractional part and a sign:
+///
+/// * A `.` between digits starts a fractional part, so `0.25` sorts before `0.5` and `1.5`
+///   sorts after `1.10`.
+/// * A `-` directly before digits is a minus sign when it starts the string or follows a
+///   non-alphanumeric character, so `-3` sorts before `-1`. After a letter or digit it stays a
+///   separator, keeping names like `file-3` and dates like `2024-05` intact.
+///
+/// Names without such numbers compare exactly as with [`natural_sort`]. Note that dotted
+/// versions like `1.9` and `1.10` are compared as decimals; use [`version_sort`] for those.
+///
+/// Path comparators use this for [`SortOrder::Decimal`].
+pub fn natural_sort_with_decimals(a: &str, b: &str) -> Ordering {
+    let mut a_rest = a;
+    let mut b_rest = b;
+    let mut a_previous = None;
+    let mut b_previous = None;
+
+    loop {
+        if let (Some(a_number), Some(b_number)) = (
+            signed_decimal_prefix(a_rest, a_previous),
+            signed_decimal_prefix(b_rest, b_previous),
+        ) {
+            match compare_signed_decimals(a_number, b_number) {
+                Ordering::Equal => {
+                    a_rest = &a_rest[a_number.len()..];
+                    b_rest = &b_rest[b_number.len()..];
+                    a_previous = a_number.chars().last();
+                    b_previous = b_number.chars().last();
+                    continue;
+                }
+                ordering => return ordering,
+            }
+        }
+
+        match (a_rest.chars().next(), b_rest.chars().next()) {
+            (None, None) => return b.cmp(a),
+            (None, _) => return Ordering::Less,
+            (_, None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) => match a_char
+                .to_ascii_lowercase()
+                .cmp(&b_char.to_ascii_lowercase())
+            {
+                Ordering::Equal => {
+                    a_rest = &a_rest[a_char.len_utf8()..];
+                    b_rest = &b_rest[b_char.len_utf8()..];
+                    a_previous = Some(a_char);
+                    b_previous = Some(b_char);
+                }
+                ordering => return ordering,
+            },
+        }
+    }
+}
+
+/// Returns the number at the start of `text`, including a minus sign and a fractional part if
+/// present. `previous` is the character preceding `text`, which decides whether a `-` is a sign.
+fn signed_decimal_prefix(text: &str, previous: Option<char>) -> Option<&str> {
+    let sign_len = if text.starts_with('-') && previous.is_none_or(|c| !c.is_alphanumeric()) {
+        1
+    } else {
+        0
+    };
+    let digits_len = |text: &str| {
+        text.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len())
+    };
+
+    let integer_len = digits_len(&text[sign_len..]);
+    if integer_len == 0 {
+        return None;
+    }
+    let mut len = sign_len + integer_len;
+    if let Some(fraction) = text[len..].strip_prefix('.') {
+        let fraction_len = digits_len(fraction);
+        if fraction_len > 0 {
+            len += 1 + fraction_len;
+        }
+    }
+    Some(&text[..len])
+}
+
+fn compare_signed_decimals(a: &str, b: &str) -> Ordering {
+    let (a_negative, a_magnitude) = a.strip_prefix('-').map_or((false, a), |m| (true, m));
+    let (b_negative, b_magnitude) = b.strip_prefix('-').map_or((false, b), |m| (true, m));
+
+    let ordering = match (a_negative, b_negative) {
+        (false, true) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        (false, false) => compare_decimal_magnitudes(a_magnitude, b_magnitude),
+        (true, true) => compare_decimal_magnitudes(b_magnitude, a_magnitude),
+    };
+    // Same value, longer one is greater (leading zeros), matching `compare_numeric_segments`.
+    ordering.then_with(|| a.len().cmp(&b.len()))
+}
+
+fn compare_decimal_magnitudes(a: &str, b: &str) -> Ordering {
+    let (a_integer, a_fraction) = a.split_once('.').unwrap_or((a, ""));
+    let (b_integer, b_fraction) = b.split_once('.').unwrap_or((b, ""));
+    compare_digit_runs(a_integer, b_integer).then_with(|| {
+        a_fraction
+            .trim_end_matches('0')
+            .cmp(b_fraction.trim_end_matches('0'))
+    })
+}
+
+/// Performs a version-aware comparison between two strings.
+///
+/// Works like [`natural_sort`], except that digit runs joined by dots (e.g. `18.10.0`) are
+/// compared as a single version, component by component, with each component compared by its
+/// numeric value. When one version is a prefix of the other, the one with fewer components
+/// sorts first, so `1.2` comes before `1.2.0`.
+///
+/// Strings that compare equal this way (such as `1.02` and `1.2`) fall back to [`natural_sort`]
+/// so the ordering stays deterministic.
+pub fn version_sort(a: &str, b: &str) -> Ordering {
+    let mut a_rest = a;
+    let mut b_rest = b;
+
+    loop {
+        match (a_rest.chars().next(), b_rest.chars().next()) {
+            (None, None) => return natural_sort(a, b),
+            (None, _) => return Ordering::Less,
+            (_, None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) => {
+                if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+                    let (a_components, a_remainder) = split_version(a_rest);
+                    let (b_components, b_remainder) = split_version(b_rest);
+                    let ordering = a_components
+                        .iter()
+                        .zip(&b_components)
+                        .map(|(a_component, b_component)| {
+                            compare_digit_runs(a_component, b_component)
+                        })
+                        .find(|ordering| !ordering.is_eq())
+                        .unwrap_or_else(|| a_components.len().cmp(&b_components.len()));
+                    if !ordering.is_eq() {
+                        return ordering;
+                    }
+                    a_rest = a_remainder;
+                    b_rest = b_remainder;
                 } else {
                     match a_char
                         .to_ascii_lowercase()
                         .cmp(&b_char.to_ascii_lowercase())
                     {
                         Ordering::Equal => {
-                            a_iter.next();
-                            b_iter.next();
+                            a_rest = &a_rest[a_char.len_utf8()..];
+                            b_rest = &b_rest[b_char.len_utf8()..];
                         }
                         ordering => return ordering,
                     }
@@ -1172,6 +2316,36 @@ pub fn natural_sort(a: &str, b: &str) -> Ordering {
     }
 }
 
+/// Splits a leading dotted version like `18.10.0` off of `text`, returning its numeric
+/// components and the remaining text. A trailing dot not followed by a digit is left in the
+/// remainder.
+fn split_version(text: &str) -> (Vec<&str>, &str) {
+    let mut components = Vec::new();
+    let mut rest = text;
+    loop {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        components.push(&rest[..end]);
+        rest = &rest[end..];
+        match rest.strip_prefix('.') {
+            Some(after_dot) if after_dot.starts_with(|c: char| c.is_ascii_digit()) => {
+                rest = after_dot;
+            }
+            _ => break,
+        }
+    }
+    (components, rest)
+}
+
+/// Compares two runs of ASCII digits by numeric value without parsing them, so arbitrarily long
+/// runs can't overflow.
+fn compare_digit_runs(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
 /// Case-insensitive natural sort without applying the final lowercase/uppercase tie-breaker.
 /// This is useful when comparing individual path components where we want to keep walking
 /// deeper components before deciding on casing.
@@ -1223,6 +2397,17 @@ pub enum SortOrder {
     /// Pure Unicode codepoint comparison. No case folding, no natural number sorting.
     /// Uppercase ASCII sorts before lowercase. Accented characters sort after ASCII.
     Unicode,
+    /// Natural sort with Unicode case folding instead of ASCII-only case folding, so accented
+    /// and non-Latin letters group with their other case (e.g., `Ärger` next to `ärmel`).
+    /// Lowercase is preferred in ties.
+    CaseFolded,
+    /// Case-insensitive natural sort that compares dotted numeric groups as versions,
+    /// component by component (e.g., `v9.1.0` before `v18.0.0` before `v18.10.0`).
+    Version,
+    /// Case-insensitive natural sort that reads decimals and negative numbers by value
+    /// (e.g., `scale-0.25` before `scale-0.5`, `temp_-3` before `temp_-1`).
+    /// See [`natural_sort_with_decimals`] for the exact rules.
+    Decimal,
 }
 
 /// Controls how files and directories are ordered relative to each other.
@@ -1237,6 +2422,35 @@ pub enum SortMode {
     FilesFirst,
 }
 
+/// Controls whether names are ordered ascending (A to Z) or descending (Z to A).
+///
+/// The direction only applies to name comparisons within a group: the grouping chosen by
+/// [`SortMode`] is kept, and parent directories still precede their contents.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Every setting that decides how [`compare_rel_paths_with`] and [`compare_paths_with`] order
+/// paths, so callers can pass their sorting settings along whole instead of unpacking each field.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathSortSettings {
+    pub mode: SortMode,
+    pub order: SortOrder,
+    pub direction: SortDirection,
+}
+
+impl SortDirection {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+}
+
 fn case_group_key(name: &str, order: SortOrder) -> u8 {
     let first = match name.chars().next() {
         Some(c) => c,
@@ -1264,6 +2478,9 @@ fn case_group_key(name: &str, order: SortOrder) -> u8 {
 fn compare_strings(a: &str, b: &str, order: SortOrder) -> Ordering {
     match order {
         SortOrder::Unicode => a.cmp(b),
+        SortOrder::Version => version_sort(a, b),
+        SortOrder::Decimal => natural_sort_with_decimals(a, b),
+        SortOrder::CaseFolded => natural_sort_case_folded(a, b),
         _ => natural_sort(a, b),
     }
 }
@@ -1271,6 +2488,17 @@ fn compare_strings(a: &str, b: &str, order: SortOrder) -> Ordering {
 fn compare_strings_no_tiebreak(a: &str, b: &str, order: SortOrder) -> Ordering {
     match order {
         SortOrder::Unicode => a.cmp(b),
+        SortOrder::Version | SortOrder::Decimal if a.eq_ignore_ascii_case(b) => Ordering::Equal,
+        SortOrder::Version => version_sort(a, b),
+        SortOrder::Decimal => natural_sort_with_decimals(a, b),
+        SortOrder::CaseFolded
+            if a.chars()
+                .flat_map(char::to_lowercase)
+                .eq(b.chars().flat_map(char::to_lowercase)) =>
+        {
+            Ordering::Equal
+        }
+        SortOrder::CaseFolded => natural_sort_case_folded(a, b),
         _ => natural_sort_no_tiebreak(a, b),
     }
 }
@@ -1284,15 +2512,41 @@ pub fn compare_rel_paths(
         (path_b, b_is_file),
         SortMode::DirectoriesFirst,
         SortOrder::Default,
+        SortDirection::Ascending,
     )
 }
 
+/// Equivalent to [`compare_rel_paths_with`] for settings given one by one.
 pub fn compare_rel_paths_by(
-    (path_a, a_is_file): (&RelPath, bool),
-    (path_b, b_is_file): (&RelPath, bool),
+    a: (&RelPath, bool),
+    b: (&RelPath, bool),
     mode: SortMode,
     order: SortOrder,
+    direction: SortDirection,
+) -> Ordering {
+    compare_rel_paths_with(
+        a,
+        b,
+        &PathSortSettings {
+            mode,
+            order,
+            direction,
+        },
+    )
+}
+
+/// Compares two worktree paths, each paired with whether it's a file, as the sorting settings
+/// ask, keeping every directory ahead of its own contents.
+pub fn compare_rel_paths_with(
+    (path_a, a_is_file): (&RelPath, bool),
+    (path_b, b_is_file): (&RelPath, bool),
+    settings: &PathSortSettings,
 ) -> Ordering {
+    let PathSortSettings {
+        mode,
+        order,
+        direction,
+    } = *settings;
     let needs_final_tiebreak =
         mode != SortMode::DirectoriesFirst && !(std::ptr::eq(path_a, path_b) || path_a == path_b);
 
@@ -1374,16 +2628,21 @@ pub fn compare_rel_paths_by(
                 };
 
                 if !ordering.is_eq() {
-                    return ordering;
+                    return direction.apply(ordering);
                 }
             }
             (Some(_), None) => return Ordering::Greater,
             (None, Some(_)) => return Ordering::Less,
             (None, None) => {
-                if needs_final_tiebreak {
-                    return compare_strings(path_a.as_unix_str(), path_b.as_unix_str(), order);
-                }
-                return Ordering::Equal;
+                let ordering = if needs_final_tiebreak {
+                    compare_strings(path_a.as_unix_str(), path_b.as_unix_str(), order)
+                } else {
+                    Ordering::Equal
+                };
+                // Distinct paths that are still equal under the chosen order, such as `a.RS` and
+                // `a.rs`, fall back to their raw bytes so the result doesn't depend on input order.
+                return direction
+                    .apply(ordering.then_with(|| path_a.as_unix_str().cmp(path_b.as_unix_str())));
             }
         }
     }
@@ -1393,6 +2652,38 @@ pub fn compare_paths(
     (path_a, a_is_file): (&Path, bool),
     (path_b, b_is_file): (&Path, bool),
 ) -> Ordering {
+    compare_paths_by(
+        (path_a, a_is_file),
+        (path_b, b_is_file),
+        SortMode::DirectoriesFirst,
+    )
+}
+
+/// Equivalent to [`compare_paths_with`] with the default order and direction.
+pub fn compare_paths_by(a: (&Path, bool), b: (&Path, bool), mode: SortMode) -> Ordering {
+    compare_paths_with(
+        a,
+        b,
+        &PathSortSettings {
+            mode,
+            ..PathSortSettings::default()
+        },
+    )
+}
+
+/// Compares two paths, each paired with whether it's a file, as the sorting settings ask.
+/// Like [`compare_rel_paths_with`], the direction only reverses names within a group, and
+/// every directory stays ahead of its own contents.
+pub fn compare_paths_with(
+    (path_a, a_is_file): (&Path, bool),
+    (path_b, b_is_file): (&Path, bool),
+    settings: &PathSortSettings,
+) -> Ordering {
+    let PathSortSettings {
+        mode,
+        order,
+        direction,
+    } = *settings;
     let mut components_a = path_a.components().peekable();
     let mut components_b = path_b.components().peekable();
 
@@ -1402,7 +2693,13 @@ pub fn compare_paths(
                 let a_is_file = components_a.peek().is_none() && a_is_file;
                 let b_is_file = components_b.peek().is_none() && b_is_file;
 
-                let ordering = a_is_file.cmp(&b_is_file).then_with(|| {
+                let file_dir_ordering = match mode {
+                    SortMode::DirectoriesFirst => a_is_file.cmp(&b_is_file),
+                    SortMode::FilesFirst => b_is_file.cmp(&a_is_file),
+                    SortMode::Mixed => Ordering::Equal,
+                };
+
+                let ordering = file_dir_ordering.then_with(|| {
                     let path_a = Path::new(component_a.as_os_str());
                     let path_string_a = if a_is_file {
                         path_a.file_stem()
@@ -1420,13 +2717,15 @@ pub fn compare_paths(
                     .map(|s| s.to_string_lossy());
 
                     let compare_components = match (path_string_a, path_string_b) {
-                        (Some(a), Some(b)) => natural_sort(&a, &b),
+                        (Some(a), Some(b)) => case_group_key(&a, order)
+                            .cmp(&case_group_key(&b, order))
+                            .then_with(|| compare_strings(&a, &b, order)),
                         (Some(_), None) => Ordering::Greater,
                         (None, Some(_)) => Ordering::Less,
                         (None, None) => Ordering::Equal,
                     };
 
-                    compare_components.then_with(|| {
+                    direction.apply(compare_components.then_with(|| {
                         if a_is_file && b_is_file {
                             let ext_a = path_a.extension().unwrap_or_default();
                             let ext_b = path_b.extension().unwrap_or_default();
@@ -1434,7 +2733,7 @@ pub fn compare_paths(
                         } else {
                             Ordering::Equal
                         }
-                    })
+                    }))
                 });
 
                 if !ordering.is_eq() {
@@ -1448,6 +2747,27 @@ pub fn compare_paths(
     }
 }
 
+/// Sorts `(path, is_file)` entries in place with [`compare_paths_with`].
+pub fn sort_paths_by<P: AsRef<Path>>(entries: &mut [(P, bool)], settings: &PathSortSettings) {
+    entries.sort_by(|(path_a, a_is_file), (path_b, b_is_file)| {
+        compare_paths_with(
+            (path_a.as_ref(), *a_is_file),
+            (path_b.as_ref(), *b_is_file),
+            settings,
+        )
+    });
+}
+
+/// Collects `(path, is_file)` entries into a `Vec` sorted with [`compare_paths_with`].
+pub fn sorted_paths_by<P: AsRef<Path>>(
+    entries: impl IntoIterator<Item = (P, bool)>,
+    settings: &PathSortSettings,
+) -> Vec<(P, bool)> {
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    sort_paths_by(&mut entries, settings);
+    entries
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WslPath {
     pub distro: String,
@@ -1692,6 +3012,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_uses_path_style_separator() {
+        let path = Path::new("dev/zed/crates");
+        assert_eq!(PathStyle::Posix.render(path), "dev/zed/crates");
+        assert_eq!(PathStyle::Windows.render(path), "dev\\zed\\crates");
+
+        if cfg!(target_os = "windows") {
+            let path = Path::new("C:\\Users\\user/dev");
+            assert_eq!(PathStyle::Posix.render(path), "C:/Users/user/dev");
+            assert_eq!(PathStyle::Windows.render(path), "C:\\Users\\user\\dev");
+        } else {
+            let path = Path::new("/home/user/back\\slash");
+            assert_eq!(PathStyle::Posix.render(path), "/home/user/back\\slash");
+            assert_eq!(PathStyle::Windows.render(path), "\\home\\user\\back\\slash");
+        }
+    }
+
     fn rel_path_entry(path: &'static str, is_file: bool) -> (&'static RelPath, bool) {
         (RelPath::unix(path).unwrap(), is_file)
     }
@@ -1701,7 +3038,7 @@ mod tests {
         mode: SortMode,
         order: SortOrder,
     ) -> Vec<(&'static RelPath, bool)> {
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, mode, order));
+        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, mode, order, SortDirection::Ascending));
         paths
     }
 
@@ -1744,40 +3081,203 @@ mod tests {
     }
 
     #[perf]
-    fn compare_paths_with_same_name_different_extensions() {
+    fn compare_paths_files_first() {
         let mut paths = vec![
-            (Path::new("test_dirs/file.rs"), true),
-            (Path::new("test_dirs/file.txt"), true),
-            (Path::new("test_dirs/file.md"), true),
-            (Path::new("test_dirs/file"), true),
-            (Path::new("test_dirs/file.a"), true),
+            (Path::new("test_dirs"), false),
+            (Path::new("test_dirs/1.46"), false),
+            (Path::new("test_dirs/1.46/bar_1"), true),
+            (Path::new("test_dirs/1.45"), false),
+            (Path::new("test_dirs/1.45/foo_2"), true),
+            (Path::new("test_dirs/1.45/foo_1"), true),
+            (Path::new("test_file_2"), true),
+            (Path::new("test_file_10"), true),
+            (Path::new("a_file"), true),
         ];
-        paths.sort_by(|&a, &b| compare_paths(a, b));
+        paths.sort_by(|&a, &b| compare_paths_by(a, b, SortMode::FilesFirst));
         assert_eq!(
             paths,
             vec![
-                (Path::new("test_dirs/file"), true),
-                (Path::new("test_dirs/file.a"), true),
-                (Path::new("test_dirs/file.md"), true),
-                (Path::new("test_dirs/file.rs"), true),
-                (Path::new("test_dirs/file.txt"), true),
+                (Path::new("a_file"), true),
+                (Path::new("test_file_2"), true),
+                (Path::new("test_file_10"), true),
+                (Path::new("test_dirs"), false),
+                (Path::new("test_dirs/1.45"), false),
+                (Path::new("test_dirs/1.45/foo_1"), true),
+                (Path::new("test_dirs/1.45/foo_2"), true),
+                (Path::new("test_dirs/1.46"), false),
+                (Path::new("test_dirs/1.46/bar_1"), true),
             ]
         );
     }
 
     #[perf]
-    fn compare_paths_case_semi_sensitive() {
-        let mut paths = vec![
-            (Path::new("test_DIRS"), false),
-            (Path::new("test_DIRS/foo_1"), true),
-            (Path::new("test_DIRS/foo_2"), true),
-            (Path::new("test_DIRS/bar"), true),
-            (Path::new("test_DIRS/BAR"), true),
-            (Path::new("test_dirs"), false),
-            (Path::new("test_dirs/foo_1"), true),
-            (Path::new("test_dirs/foo_2"), true),
-            (Path::new("test_dirs/bar"), true),
-            (Path::new("test_dirs/BAR"), true),
+    fn sort_paths_by_matches_compare_paths_with() {
+        let entries = vec![
+            (PathBuf::from("zeta/beta.txt"), true),
+            (PathBuf::from("mu.rs"), true),
+            (PathBuf::from("zeta"), false),
+            (PathBuf::from("file10.txt"), true),
+            (PathBuf::from("beta"), false),
+            (PathBuf::from("file2.txt"), true),
+            (PathBuf::from("zeta/alpha"), false),
+        ];
+
+        for mode in [
+            SortMode::DirectoriesFirst,
+            SortMode::Mixed,
+            SortMode::FilesFirst,
+        ] {
+            for direction in [SortDirection::Ascending, SortDirection::Descending] {
+                let settings = PathSortSettings {
+                    mode,
+                    order: SortOrder::Default,
+                    direction,
+                };
+                let mut expected = entries.clone();
+                expected.sort_by(|(path_a, a_is_file), (path_b, b_is_file)| {
+                    compare_paths_with((path_a, *a_is_file), (path_b, *b_is_file), &settings)
+                });
+
+                let mut sorted_in_place = entries.clone();
+                sort_paths_by(&mut sorted_in_place, &settings);
+                assert_eq!(sorted_in_place, expected, "{settings:?}");
+                assert_eq!(
+                    sorted_paths_by(entries.clone(), &settings),
+                    expected,
+                    "{settings:?}"
+                );
+            }
+        }
+
+        let mixed = PathSortSettings {
+            mode: SortMode::Mixed,
+            ..PathSortSettings::default()
+        };
+        assert_eq!(
+            sorted_paths_by(entries, &mixed)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("beta"),
+                PathBuf::from("file2.txt"),
+                PathBuf::from("file10.txt"),
+                PathBuf::from("mu.rs"),
+                PathBuf::from("zeta"),
+                PathBuf::from("zeta/alpha"),
+                PathBuf::from("zeta/beta.txt"),
+            ]
+        );
+    }
+
+    #[perf]
+    fn compare_paths_with_order_and_direction() {
+        let settings = PathSortSettings {
+            mode: SortMode::DirectoriesFirst,
+            order: SortOrder::Upper,
+            direction: SortDirection::Descending,
+        };
+        let entries = vec![
+            (Path::new("assets"), false),
+            (Path::new("Docs"), false),
+            (Path::new("Docs/b.md"), true),
+            (Path::new("Docs/a.md"), true),
+            (Path::new("build.rs"), true),
+            (Path::new("Readme.md"), true),
+        ];
+        assert_eq!(
+            sorted_paths_by(entries, &settings),
+            vec![
+                (Path::new("assets"), false),
+                (Path::new("Docs"), false),
+                (Path::new("Docs/b.md"), true),
+                (Path::new("Docs/a.md"), true),
+                (Path::new("build.rs"), true),
+                (Path::new("Readme.md"), true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn compare_paths_mixed_interleaves_files_and_directories() {
+        let mut paths = vec![
+            (Path::new("zeta"), false),
+            (Path::new("zeta/beta.txt"), true),
+            (Path::new("zeta/alpha"), false),
+            (Path::new("mu.rs"), true),
+            (Path::new("alpha.txt"), true),
+            (Path::new("beta"), false),
+        ];
+        paths.sort_by(|&a, &b| compare_paths_by(a, b, SortMode::Mixed));
+        assert_eq!(
+            paths,
+            vec![
+                (Path::new("alpha.txt"), true),
+                (Path::new("beta"), false),
+                (Path::new("mu.rs"), true),
+                (Path::new("zeta"), false),
+                (Path::new("zeta/alpha"), false),
+                (Path::new("zeta/beta.txt"), true),
+            ]
+        );
+
+        let mut rel_paths = vec![
+            rel_path_entry("zeta", false),
+            rel_path_entry("zeta/beta.txt", true),
+            rel_path_entry("zeta/alpha", false),
+            rel_path_entry("mu.rs", true),
+            rel_path_entry("alpha.txt", true),
+            rel_path_entry("beta", false),
+        ];
+        rel_paths = sorted_rel_paths(rel_paths, SortMode::Mixed, SortOrder::Default);
+        assert_eq!(
+            rel_paths,
+            vec![
+                rel_path_entry("alpha.txt", true),
+                rel_path_entry("beta", false),
+                rel_path_entry("mu.rs", true),
+                rel_path_entry("zeta", false),
+                rel_path_entry("zeta/alpha", false),
+                rel_path_entry("zeta/beta.txt", true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn compare_paths_with_same_name_different_extensions() {
+        let mut paths = vec![
+            (Path::new("test_dirs/file.rs"), true),
+            (Path::new("test_dirs/file.txt"), true),
+            (Path::new("test_dirs/file.md"), true),
+            (Path::new("test_dirs/file"), true),
+            (Path::new("test_dirs/file.a"), true),
+        ];
+        paths.sort_by(|&a, &b| compare_paths(a, b));
+        assert_eq!(
+            paths,
+            vec![
+                (Path::new("test_dirs/file"), true),
+                (Path::new("test_dirs/file.a"), true),
+                (Path::new("test_dirs/file.md"), true),
+                (Path::new("test_dirs/file.rs"), true),
+                (Path::new("test_dirs/file.txt"), true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn compare_paths_case_semi_sensitive() {
+        let mut paths = vec![
+            (Path::new("test_DIRS"), false),
+            (Path::new("test_DIRS/foo_1"), true),
+            (Path::new("test_DIRS/foo_2"), true),
+            (Path::new("test_DIRS/bar"), true),
+            (Path::new("test_DIRS/BAR"), true),
+            (Path::new("test_dirs"), false),
+            (Path::new("test_dirs/foo_1"), true),
+            (Path::new("test_dirs/foo_2"), true),
+            (Path::new("test_dirs/bar"), true),
+            (Path::new("test_dirs/BAR"), true),
         ];
         paths.sort_by(|&a, &b| compare_paths(a, b));
         assert_eq!(
@@ -1834,7 +3334,15 @@ mod tests {
             (RelPath::unix("Carrot").unwrap(), false),
             (RelPath::unix("aardvark.txt").unwrap(), true),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         // Case-insensitive: aardvark < Apple < banana < Carrot < zebra
         assert_eq!(
             paths,
@@ -1848,6 +3356,86 @@ mod tests {
         );
     }
 
+    #[perf]
+    fn compare_rel_paths_with_settings() {
+        let settings = PathSortSettings {
+            mode: SortMode::FilesFirst,
+            order: SortOrder::Upper,
+            direction: SortDirection::Descending,
+        };
+        let mut paths = vec![
+            rel_path_entry("src", false),
+            rel_path_entry("src/lib.rs", true),
+            rel_path_entry("Readme.md", true),
+            rel_path_entry("build.rs", true),
+            rel_path_entry("Docs", false),
+            rel_path_entry("assets", false),
+        ];
+        paths.sort_by(|&a, &b| compare_rel_paths_with(a, b, &settings));
+        assert_eq!(
+            paths,
+            vec![
+                rel_path_entry("build.rs", true),
+                rel_path_entry("Readme.md", true),
+                rel_path_entry("src", false),
+                rel_path_entry("src/lib.rs", true),
+                rel_path_entry("assets", false),
+                rel_path_entry("Docs", false),
+            ]
+        );
+
+        for (a, b) in paths.iter().copied().tuple_combinations() {
+            assert_eq!(
+                compare_rel_paths_with(a, b, &settings),
+                compare_rel_paths_by(a, b, settings.mode, settings.order, settings.direction)
+            );
+        }
+        assert_eq!(
+            PathSortSettings::default(),
+            PathSortSettings {
+                mode: SortMode::DirectoriesFirst,
+                order: SortOrder::Default,
+                direction: SortDirection::Ascending,
+            }
+        );
+    }
+
+    #[perf]
+    fn compare_rel_paths_descending_keeps_directories_first() {
+        let mut paths = vec![
+            rel_path_entry("apple.txt", true),
+            rel_path_entry("src", false),
+            rel_path_entry("src/b.rs", true),
+            rel_path_entry("src/a.rs", true),
+            rel_path_entry("zebra.txt", true),
+            rel_path_entry("docs", false),
+            rel_path_entry("file2.txt", true),
+            rel_path_entry("file10.txt", true),
+        ];
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::DirectoriesFirst,
+                SortOrder::Default,
+                SortDirection::Descending,
+            )
+        });
+        assert_eq!(
+            paths,
+            vec![
+                rel_path_entry("src", false),
+                rel_path_entry("src/b.rs", true),
+                rel_path_entry("src/a.rs", true),
+                rel_path_entry("docs", false),
+                rel_path_entry("zebra.txt", true),
+                rel_path_entry("file10.txt", true),
+                rel_path_entry("file2.txt", true),
+                rel_path_entry("apple.txt", true),
+            ]
+        );
+    }
+
     #[perf]
     fn compare_rel_paths_files_first_basic() {
         // Test that files come before directories
@@ -1858,8 +3446,15 @@ mod tests {
             (RelPath::unix("Carrot").unwrap(), false),
             (RelPath::unix("aardvark.txt").unwrap(), true),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         // Files first (case-insensitive), then directories (case-insensitive)
         assert_eq!(
             paths,
@@ -1883,8 +3478,15 @@ mod tests {
             (RelPath::unix("carrot").unwrap(), false),
             (RelPath::unix("Aardvark.txt").unwrap(), true),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -1907,8 +3509,15 @@ mod tests {
             (RelPath::unix("dir10").unwrap(), false),
             (RelPath::unix("file1.txt").unwrap(), true),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -1929,7 +3538,15 @@ mod tests {
             (RelPath::unix("readme.txt").unwrap(), true),
             (RelPath::unix("ReadMe.rs").unwrap(), true),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         // All "readme" variants should group together, sorted by extension
         assert_eq!(
             paths,
@@ -1950,7 +3567,15 @@ mod tests {
             (RelPath::unix("file1.txt").unwrap(), true),
             (RelPath::unix("dir2").unwrap(), false),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         // Case-insensitive: dir1, dir2, file1, file2 (all mixed)
         assert_eq!(
             paths,
@@ -1969,7 +3594,15 @@ mod tests {
             (RelPath::unix("Hello.txt").unwrap(), true),
             (RelPath::unix("hello").unwrap(), false),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -1982,7 +3615,15 @@ mod tests {
             (RelPath::unix("hello").unwrap(), false),
             (RelPath::unix("Hello.txt").unwrap(), true),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2001,7 +3642,15 @@ mod tests {
             (RelPath::unix("src").unwrap(), false),
             (RelPath::unix("target").unwrap(), false),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2022,8 +3671,15 @@ mod tests {
             (RelPath::unix("src").unwrap(), false),
             (RelPath::unix("tests").unwrap(), false),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2044,7 +3700,15 @@ mod tests {
             (RelPath::unix(".github").unwrap(), false),
             (RelPath::unix("src").unwrap(), false),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2065,8 +3729,15 @@ mod tests {
             (RelPath::unix(".github").unwrap(), false),
             (RelPath::unix("src").unwrap(), false),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2086,7 +3757,15 @@ mod tests {
             (RelPath::unix("file.md").unwrap(), true),
             (RelPath::unix("file.txt").unwrap(), true),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2105,8 +3784,15 @@ mod tests {
             (RelPath::unix("main.c").unwrap(), true),
             (RelPath::unix("main").unwrap(), false),
         ];
-        paths
-            .sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::FilesFirst, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::FilesFirst,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2126,7 +3812,15 @@ mod tests {
             (RelPath::unix("a.txt").unwrap(), true),
             (RelPath::unix("A.txt").unwrap(), true),
         ];
-        paths.sort_by(|&a, &b| compare_rel_paths_by(a, b, SortMode::Mixed, SortOrder::Default));
+        paths.sort_by(|&a, &b| {
+            compare_rel_paths_by(
+                a,
+                b,
+                SortMode::Mixed,
+                SortOrder::Default,
+                SortDirection::Ascending,
+            )
+        });
         assert_eq!(
             paths,
             vec![
@@ -2346,6 +4040,81 @@ mod tests {
         );
     }
 
+    #[perf]
+    fn compare_rel_paths_version() {
+        let paths = vec![
+            rel_path_entry("node-v18.10.0", false),
+            rel_path_entry("node-v9.1.0", false),
+            rel_path_entry("node-v18.0.0", false),
+            rel_path_entry("node-v18.0", false),
+            rel_path_entry("node-v18.2.0", false),
+            rel_path_entry("release-1.2.0.txt", true),
+            rel_path_entry("release-1.10.txt", true),
+            rel_path_entry("release-1.2.txt", true),
+        ];
+        assert_eq!(
+            sorted_rel_paths(paths, SortMode::DirectoriesFirst, SortOrder::Version),
+            vec![
+                rel_path_entry("node-v9.1.0", false),
+                rel_path_entry("node-v18.0", false),
+                rel_path_entry("node-v18.0.0", false),
+                rel_path_entry("node-v18.2.0", false),
+                rel_path_entry("node-v18.10.0", false),
+                rel_path_entry("release-1.2.txt", true),
+                rel_path_entry("release-1.2.0.txt", true),
+                rel_path_entry("release-1.10.txt", true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn compare_rel_paths_decimal() {
+        let paths = vec![
+            rel_path_entry("scale-0.5", false),
+            rel_path_entry("scale-0.25", false),
+            rel_path_entry("temp_-1.csv", true),
+            rel_path_entry("temp_-3.csv", true),
+            rel_path_entry("temp_2.csv", true),
+            rel_path_entry("file-1.10.txt", true),
+            rel_path_entry("file-1.5.txt", true),
+        ];
+        assert_eq!(
+            sorted_rel_paths(paths, SortMode::DirectoriesFirst, SortOrder::Decimal),
+            vec![
+                rel_path_entry("scale-0.25", false),
+                rel_path_entry("scale-0.5", false),
+                rel_path_entry("file-1.10.txt", true),
+                rel_path_entry("file-1.5.txt", true),
+                rel_path_entry("temp_-3.csv", true),
+                rel_path_entry("temp_-1.csv", true),
+                rel_path_entry("temp_2.csv", true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn test_version_sort() {
+        assert_eq!(version_sort("1.2", "1.2.0"), Ordering::Less);
+        assert_eq!(version_sort("1.2.0", "1.2"), Ordering::Greater);
+        assert_eq!(version_sort("1.2.0", "1.2.0"), Ordering::Equal);
+        assert_eq!(version_sort("v9.1.0", "v18.0.0"), Ordering::Less);
+        assert_eq!(version_sort("v18.10.0", "v18.9.1"), Ordering::Greater);
+        assert_eq!(version_sort("1.2.3.4", "1.2.10"), Ordering::Less);
+        assert_eq!(
+            version_sort("app-2.0-beta", "app-2.0-alpha"),
+            Ordering::Greater
+        );
+        assert_eq!(version_sort("App-1.0", "app-1.1"), Ordering::Less);
+        // A dot that isn't followed by a digit ends the version.
+        assert_eq!(version_sort("1.2.txt", "1.2.0.txt"), Ordering::Less);
+        // Equal numeric values fall back to natural sort for a stable order.
+        assert_eq!(version_sort("1.02", "1.2"), natural_sort("1.02", "1.2"));
+        assert_eq!(
+            version_sort("999999999999999999999999.1", "1000000000000000000000000.0"),
+            Ordering::Less
+        );
+    }
+
     #[perf]
     fn compare_rel_paths_unicode() {
         let directories_only_paths = vec![
@@ -2474,7 +4243,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file"),
                 row: None,
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2483,7 +4254,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("a:bc:.zip"),
                 row: Some(1),
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2492,7 +4265,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("one.second.zip"),
                 row: Some(1),
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2502,7 +4277,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file"),
                 row: Some(10),
-                column: Some(1)
+                column: Some(1),
+                offset: None,
+                context: None,
             }
         );
 
@@ -2511,7 +4288,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file.rs"),
                 row: None,
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2520,7 +4299,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file.rs"),
                 row: Some(1),
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2529,7 +4310,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("ab\ncd"),
                 row: None,
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2538,7 +4321,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("👋\nab"),
                 row: None,
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2548,6 +4333,8 @@ mod tests {
                 path: PathBuf::from("Types.hs"),
                 row: Some(617),
                 column: Some(9),
+                offset: None,
+                context: None,
             }
         );
 
@@ -2556,7 +4343,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("main (1).log"),
                 row: None,
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
     }
@@ -2570,6 +4359,8 @@ mod tests {
                 path: PathBuf::from("foo/bar"),
                 row: Some(34),
                 column: None,
+                offset: None,
+                context: None,
             }
         );
         assert_eq!(
@@ -2577,7 +4368,9 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("foo/bar.rs:1902"),
                 row: Some(15),
-                column: None
+                column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2587,6 +4380,8 @@ mod tests {
                 path: PathBuf::from("app-editors:zed-0.143.6:20240710-201212.log"),
                 row: Some(34),
                 column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2596,6 +4391,8 @@ mod tests {
                 path: PathBuf::from("crates/file_finder/src/file_finder.rs"),
                 row: Some(1902),
                 column: Some(13),
+                offset: None,
+                context: None,
             }
         );
 
@@ -2605,6 +4402,8 @@ mod tests {
                 path: PathBuf::from("crate/utils/src/test:today.log"),
                 row: Some(34),
                 column: None,
+                offset: None,
+                context: None,
             }
         );
         assert_eq!(
@@ -2613,105 +4412,461 @@ mod tests {
                 path: PathBuf::from("/testing/out/src/file_finder.odin"),
                 row: Some(7),
                 column: Some(15),
+                offset: None,
+                context: None,
             }
         );
     }
 
     #[perf]
-    #[cfg(target_os = "windows")]
-    fn path_with_position_parse_windows_path() {
+    fn path_with_position_saturates_huge_numbers() {
         assert_eq!(
-            PathWithPosition::parse_str("crates\\utils\\paths.rs"),
+            PathWithPosition::parse_str("file.rs:99999999999"),
             PathWithPosition {
-                path: PathBuf::from("crates\\utils\\paths.rs"),
-                row: None,
-                column: None
+                path: PathBuf::from("file.rs"),
+                row: Some(u32::MAX),
+                column: None,
+                offset: None,
+                context: None,
             }
         );
-
         assert_eq!(
-            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs"),
+            PathWithPosition::parse_str("file.rs:12:99999999999"),
             PathWithPosition {
-                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
-                row: None,
-                column: None
+                path: PathBuf::from("file.rs"),
+                row: Some(12),
+                column: Some(u32::MAX),
+                offset: None,
+                context: None,
             }
         );
-
         assert_eq!(
-            PathWithPosition::parse_str("C:\\Users\\someone\\main (1).log"),
+            PathWithPosition::parse_str("file.rs(99999999999,99999999999)"),
             PathWithPosition {
-                path: PathBuf::from("C:\\Users\\someone\\main (1).log"),
-                row: None,
-                column: None
+                path: PathBuf::from("file.rs"),
+                row: Some(u32::MAX),
+                column: Some(u32::MAX),
+                offset: None,
+                context: None,
             }
         );
-    }
-
-    #[perf]
-    #[cfg(target_os = "windows")]
-    fn path_with_position_parse_windows_path_with_suffix() {
         assert_eq!(
-            PathWithPosition::parse_str("crates\\utils\\paths.rs:101"),
+            PathWithPosition::parse_str("foo/bar.py:99999999999:in"),
             PathWithPosition {
-                path: PathBuf::from("crates\\utils\\paths.rs"),
-                row: Some(101),
-                column: None
+                path: PathBuf::from("foo/bar.py"),
+                row: Some(u32::MAX),
+                column: None,
+                offset: None,
+                context: None,
             }
         );
-
         assert_eq!(
-            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1:20"),
-            PathWithPosition {
-                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
-                row: Some(1),
-                column: Some(20)
-            }
+            PathWithPosition::parse_str("file.rs:4294967295").row,
+            Some(u32::MAX)
         );
+    }
 
+    #[perf]
+    fn path_with_position_context() {
+        let with_context =
+            PathWithPosition::parse_str("crates/util/src/paths.rs:10:5:  unused variable `x` ");
+        assert_eq!(with_context.path, PathBuf::from("crates/util/src/paths.rs"));
+        assert_eq!(with_context.row, Some(10));
+        assert_eq!(with_context.column, Some(5));
+        assert_eq!(with_context.context.as_deref(), Some("unused variable `x`"));
         assert_eq!(
-            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902,13)"),
-            PathWithPosition {
-                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
-                row: Some(1902),
-                column: Some(13)
-            }
+            with_context.to_string(&|path| path.to_string_lossy().into_owned()),
+            "crates/util/src/paths.rs:10:5"
         );
 
-        // Trim off trailing `:`s for otherwise valid input.
         assert_eq!(
-            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:"),
-            PathWithPosition {
-                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
-                row: Some(1902),
-                column: Some(13)
-            }
+            PathWithPosition::parse_str("crates/util/src/paths.rs:10:5:").context,
+            None
         );
-
         assert_eq!(
-            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:15:"),
-            PathWithPosition {
-                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
-                row: Some(13),
-                column: Some(15)
-            }
+            PathWithPosition::parse_str("foo/bar.py:22:in").context,
+            None
         );
-
         assert_eq!(
-            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:::15:"),
-            PathWithPosition {
-                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
-                row: Some(15),
-                column: None
-            }
+            PathWithPosition::parse_str("notes: see file.rs").context,
+            None
         );
+    }
 
-        assert_eq!(
+    #[perf]
+    fn path_with_position_ordering() {
+        let mut positions = [
+            "src/main.rs:10:5",
+            "src/lib.rs:3",
+            "src/main.rs:2",
+            "src/main.rs",
+            "src/main.rs:10",
+            "src/main.rs:10:1",
+            "Cargo.toml",
+        ]
+        .map(PathWithPosition::parse_str);
+        positions.sort();
+        assert_eq!(
+            positions
+                .map(|position| position.to_string(&|path| path.to_string_lossy().into_owned())),
+            [
+                "Cargo.toml",
+                "src/lib.rs:3",
+                "src/main.rs",
+                "src/main.rs:2",
+                "src/main.rs:10",
+                "src/main.rs:10:1",
+                "src/main.rs:10:5",
+            ]
+        );
+    }
+
+    #[perf]
+    fn path_with_position_parse_uri() {
+        let with_suffix =
+            PathWithPosition::parse_uri("file:///a.rs:10:5", PathStyle::Posix).unwrap();
+        assert_eq!(with_suffix.path, PathBuf::from("/a.rs"));
+        assert_eq!(with_suffix.row, Some(10));
+        assert_eq!(with_suffix.column, Some(5));
+
+        let with_offset =
+            PathWithPosition::parse_uri("file:///a.rs@1234", PathStyle::Posix).unwrap();
+        assert_eq!(with_offset.path, PathBuf::from("/a.rs"));
+        assert_eq!(with_offset.offset, Some(1234));
+
+        let escaped_colon =
+            PathWithPosition::parse_uri("file:///tmp/a%3A10", PathStyle::Posix).unwrap();
+        assert_eq!(
+            escaped_colon,
+            PathWithPosition::from_path(PathBuf::from("/tmp/a:10"))
+        );
+        let escaped_context =
+            PathWithPosition::parse_uri("file:///tmp/notes%3A%20todo.md%3A3", PathStyle::Posix)
+                .unwrap();
+        assert_eq!(
+            escaped_context,
+            PathWithPosition::from_path(PathBuf::from("/tmp/notes: todo.md:3"))
+        );
+        let escaped_then_suffix =
+            PathWithPosition::parse_uri("file:///tmp/a%3A10:2", PathStyle::Posix).unwrap();
+        assert_eq!(escaped_then_suffix.path, PathBuf::from("/tmp/a:10"));
+        assert_eq!(escaped_then_suffix.row, Some(2));
+
+        let windows =
+            PathWithPosition::parse_uri("file:///C:/Users/me/a%20b.rs#L2", PathStyle::Windows)
+                .unwrap();
+        assert_eq!(windows.path, PathBuf::from("C:\\Users\\me\\a b.rs"));
+        assert_eq!(windows.row, Some(2));
+
+        let unknown_fragment =
+            PathWithPosition::parse_uri("file:///a.rs#section", PathStyle::Posix).unwrap();
+        assert_eq!(
+            unknown_fragment,
+            PathWithPosition::from_path(PathBuf::from("/a.rs"))
+        );
+
+        let error = PathWithPosition::parse_uri("https://zed.dev/a.rs", PathStyle::Posix)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("https"), "unexpected error: {error}");
+        assert!(PathWithPosition::parse_uri("not a uri", PathStyle::Posix).is_err());
+    }
+
+    #[perf]
+    fn path_with_position_offset() {
+        let with_offset = PathWithPosition::parse_str("crates/util/src/paths.rs@1234");
+        assert_eq!(
+            with_offset,
+            PathWithPosition {
+                path: PathBuf::from("crates/util/src/paths.rs"),
+                row: None,
+                column: None,
+                offset: Some(1234),
+                context: None,
+            }
+        );
+        assert_eq!(
+            with_offset.to_string(&|path| path.to_string_lossy().into_owned()),
+            "crates/util/src/paths.rs@1234"
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("node_modules/@types/node"),
+            PathWithPosition::from_path(PathBuf::from("node_modules/@types/node"))
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("user@host"),
+            PathWithPosition::from_path(PathBuf::from("user@host"))
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("@12"),
+            PathWithPosition::from_path(PathBuf::from("@12"))
+        );
+
+        let with_row = PathWithPosition {
+            path: PathBuf::from("test_file.rs"),
+            row: Some(3),
+            column: None,
+            offset: Some(1234),
+            context: None,
+        };
+        assert_eq!(
+            with_row.to_string(&|path| path.to_string_lossy().into_owned()),
+            "test_file.rs:3"
+        );
+    }
+
+    #[perf]
+    fn path_with_position_parse_all() {
+        let text = "error in src/main.rs:10:5 and crates/util/src/paths.rs:42, see 'lib.rs(3,7)'";
+        let references = PathWithPosition::parse_all(text);
+        assert_eq!(
+            references
+                .iter()
+                .map(|(range, _)| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            [
+                "src/main.rs:10:5",
+                "crates/util/src/paths.rs:42",
+                "lib.rs(3,7)"
+            ]
+        );
+        assert_eq!(
+            references
+                .into_iter()
+                .map(|(_, path_with_position)| path_with_position)
+                .collect::<Vec<_>>(),
+            [
+                PathWithPosition {
+                    path: PathBuf::from("src/main.rs"),
+                    row: Some(10),
+                    column: Some(5),
+                    offset: None,
+                    context: None,
+                },
+                PathWithPosition {
+                    path: PathBuf::from("crates/util/src/paths.rs"),
+                    row: Some(42),
+                    column: None,
+                    offset: None,
+                    context: None,
+                },
+                PathWithPosition {
+                    path: PathBuf::from("lib.rs"),
+                    row: Some(3),
+                    column: Some(7),
+                    offset: None,
+                    context: None,
+                },
+            ]
+        );
+
+        let text = "see https://zed.dev/docs:3 and src/a.rs:4";
+        let references = PathWithPosition::parse_all(text);
+        assert_eq!(
+            references
+                .iter()
+                .map(|(range, _)| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            ["src/a.rs:4"]
+        );
+        assert!(PathWithPosition::parse_all("Listening on http://localhost:8080").is_empty());
+        assert!(PathWithPosition::parse_all("no references: here").is_empty());
+        assert!(PathWithPosition::parse_all("").is_empty());
+    }
+
+    #[perf]
+    fn path_with_position_custom_delimiter() {
+        let parsed = PathWithPosition::parse_str_with_delimiter("src/main.rs#10#5", '#');
+        assert_eq!(
+            parsed,
+            PathWithPosition {
+                path: PathBuf::from("src/main.rs"),
+                row: Some(10),
+                column: Some(5),
+                offset: None,
+                context: None,
+            }
+        );
+        assert_eq!(
+            parsed.to_string_with_delimiter(&|path| path.to_string_lossy().into_owned(), '#'),
+            "src/main.rs#10#5"
+        );
+
+        let row_only = PathWithPosition::parse_str_with_delimiter(" src/main.rs#10 ", '#');
+        assert_eq!(row_only.path, PathBuf::from("src/main.rs"));
+        assert_eq!((row_only.row, row_only.column), (Some(10), None));
+        assert_eq!(
+            row_only.to_string_with_delimiter(&|path| path.to_string_lossy().into_owned(), '#'),
+            "src/main.rs#10"
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str_with_delimiter("docs/c#/intro.md", '#'),
+            PathWithPosition::from_path(PathBuf::from("docs/c#/intro.md"))
+        );
+        assert_eq!(
+            PathWithPosition::parse_str_with_delimiter("src/main.rs:10:5", ':'),
+            PathWithPosition::parse_str("src/main.rs:10:5")
+        );
+        assert_eq!(
+            parsed.to_string(&|path| path.to_string_lossy().into_owned()),
+            "src/main.rs:10:5"
+        );
+    }
+
+    #[perf]
+    fn path_with_position_location_suffix() {
+        let path_with_position = |row, column| PathWithPosition {
+            path: PathBuf::from("crates/util/src/paths.rs"),
+            row,
+            column,
+            offset: None,
+            context: None,
+        };
+
+        for (position, expected_suffix) in [
+            (path_with_position(Some(10), Some(5)), Some(":10:5")),
+            (path_with_position(Some(10), None), Some(":10")),
+            (path_with_position(None, None), None),
+        ] {
+            assert_eq!(position.file_name(), Some(OsStr::new("paths.rs")));
+            assert_eq!(position.location_suffix().as_deref(), expected_suffix);
+            assert_eq!(
+                position.to_string(&|path| path.to_string_lossy().into_owned()),
+                format!(
+                    "crates/util/src/paths.rs{}",
+                    expected_suffix.unwrap_or_default()
+                )
+            );
+        }
+
+        let with_offset = PathWithPosition {
+            offset: Some(1234),
+            ..path_with_position(None, None)
+        };
+        assert_eq!(with_offset.location_suffix().as_deref(), Some("@1234"));
+        assert_eq!(
+            PathWithPosition::from_path(PathBuf::from("/")).file_name(),
+            None
+        );
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn path_with_position_parse_windows_path() {
+        assert_eq!(
+            PathWithPosition::parse_str("crates\\utils\\paths.rs"),
+            PathWithPosition {
+                path: PathBuf::from("crates\\utils\\paths.rs"),
+                row: None,
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs"),
+            PathWithPosition {
+                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
+                row: None,
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:\\Users\\someone\\main (1).log"),
+            PathWithPosition {
+                path: PathBuf::from("C:\\Users\\someone\\main (1).log"),
+                row: None,
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn path_with_position_parse_windows_path_with_suffix() {
+        assert_eq!(
+            PathWithPosition::parse_str("crates\\utils\\paths.rs:101"),
+            PathWithPosition {
+                path: PathBuf::from("crates\\utils\\paths.rs"),
+                row: Some(101),
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1:20"),
+            PathWithPosition {
+                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
+                row: Some(1),
+                column: Some(20),
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902,13)"),
+            PathWithPosition {
+                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
+                row: Some(1902),
+                column: Some(13),
+                offset: None,
+                context: None,
+            }
+        );
+
+        // Trim off trailing `:`s for otherwise valid input.
+        assert_eq!(
+            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:"),
+            PathWithPosition {
+                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
+                row: Some(1902),
+                column: Some(13),
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:15:"),
+            PathWithPosition {
+                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
+                row: Some(13),
+                column: Some(15),
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs:1902:::15:"),
+            PathWithPosition {
+                path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
+                row: Some(15),
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
             PathWithPosition::parse_str("\\\\?\\C:\\Users\\someone\\test_file.rs(1902,13):"),
             PathWithPosition {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: Some(13),
+                offset: None,
+                context: None,
             }
         );
 
@@ -2721,6 +4876,8 @@ mod tests {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: None,
+                offset: None,
+                context: None,
             }
         );
 
@@ -2730,73 +4887,868 @@ mod tests {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: Some(13),
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902,13):"),
+            PathWithPosition {
+                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
+                row: Some(1902),
+                column: Some(13),
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902):"),
+            PathWithPosition {
+                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
+                row: Some(1902),
+                column: None,
+                offset: None,
+                context: None,
+            }
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("crates/utils/paths.rs:101"),
+            PathWithPosition {
+                path: PathBuf::from("crates\\utils\\paths.rs"),
+                row: Some(101),
+                column: None,
+                offset: None,
+                context: None,
             }
         );
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn path_with_position_parse_windows_drive_letter() {
+        let parsed = |row, column| PathWithPosition {
+            path: PathBuf::from("C:\\Users\\me\\f.rs"),
+            row,
+            column,
+            offset: None,
+            context: None,
+        };
+        for (input, row, column) in [
+            ("C:/Users/me/f.rs:10:5", Some(10), Some(5)),
+            ("C:/Users/me/f.rs:10", Some(10), None),
+            ("C:\\Users\\me/f.rs:10:5", Some(10), Some(5)),
+            ("C:/Users/me/f.rs(10,5)", Some(10), Some(5)),
+            ("C:/Users/me/f.rs:10:in", Some(10), None),
+            ("C:/Users/me/f.rs", None, None),
+        ] {
+            assert_eq!(
+                PathWithPosition::parse_str(input),
+                parsed(row, column),
+                "{input:?}"
+            );
+        }
+
+        assert_eq!(
+            PathWithPosition::parse_str("C:10"),
+            PathWithPosition::from_path(PathBuf::from("C:10"))
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("C:"),
+            PathWithPosition::from_path(PathBuf::from("C:"))
+        );
+    }
+
+    #[perf]
+    fn test_path_compact() {
+        // Tests share the process-wide home directory and another test may have already
+        // resolved it, so override it with the same value that tests fall back to.
+        let test_home = if cfg!(target_os = "macos") {
+            PathBuf::from("/Users/zed")
+        } else if cfg!(target_os = "windows") {
+            PathBuf::from("C:\\Users\\zed")
+        } else {
+            PathBuf::from("/home/zed")
+        };
+        set_home_dir_override(test_home.clone()).ok();
+        assert_eq!(home_dir(), &test_home);
+
+        let path = test_home.join("some_file.txt");
+        if cfg!(any(target_os = "linux", target_os = "freebsd")) || cfg!(target_os = "macos") {
+            assert_eq!(path.compact().to_str(), Some("~/some_file.txt"));
+        } else if cfg!(target_os = "windows") {
+            assert_eq!(path.compact().to_str(), Some("~\\some_file.txt"));
+        } else {
+            assert_eq!(path.compact().to_str(), path.to_str());
+        }
+        assert_eq!(path.compact_cow(), path.compact());
+
+        let outside_home = Path::new("/tmp/some_file.txt");
+        assert!(matches!(outside_home.compact_cow(), Cow::Borrowed(_)));
+        assert_eq!(outside_home.compact(), outside_home.to_path_buf());
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_path_compact_windows() {
+        let test_home = PathBuf::from("C:\\Users\\zed");
+        set_home_dir_override(test_home.clone()).ok();
+        assert_eq!(home_dir(), &test_home);
+
+        assert_eq!(
+            Path::new("\\\\?\\C:\\Users\\zed\\some_file.txt")
+                .compact()
+                .to_str(),
+            Some("~\\some_file.txt")
+        );
+        assert_eq!(
+            Path::new("C:\\Users\\zed\\a\\b").compact().to_str(),
+            Some("~\\a\\b")
+        );
+        assert_eq!(
+            Path::new("C:\\Users\\other\\file.txt").compact(),
+            PathBuf::from("C:\\Users\\other\\file.txt")
+        );
+    }
+
+    #[perf]
+    fn test_path_compact_against() {
+        let home = home_dir();
+        let project = home.join("projects").join("zed");
+        let file = project.join("crates").join("util.rs");
+        let roots = [(project.as_path(), ""), (home.as_path(), "~")];
+
+        assert_eq!(
+            file.compact_against(&roots),
+            Path::new("crates").join("util.rs")
+        );
+        assert_eq!(
+            file.compact_against(&[(project.as_path(), "zed")]),
+            Path::new("zed").join("crates").join("util.rs")
+        );
+        assert_eq!(
+            home.join("notes.txt").compact_against(&roots),
+            Path::new("~").join("notes.txt")
+        );
+        assert_eq!(file.compact_against(&[]), file.compact());
+
+        let outside = Path::new("/tmp/other.txt");
+        assert_eq!(outside.compact_against(&roots), outside.to_path_buf());
+    }
+
+    #[perf]
+    fn test_path_strip_known_prefixes() {
+        let home = home_dir();
+        let registry = home.join(".cargo").join("registry").join("src");
+        let prefixes = [
+            (home.as_path(), "<home>"),
+            (registry.as_path(), "<registry>"),
+        ];
+
+        let source = registry.join("serde-1.0.0").join("src").join("lib.rs");
+        assert_eq!(
+            source.strip_known_prefixes(&prefixes),
+            Path::new("<registry>")
+                .join("serde-1.0.0")
+                .join("src")
+                .join("lib.rs")
+                .display()
+                .to_string()
+        );
+        assert_eq!(
+            home.join("notes.txt").strip_known_prefixes(&prefixes),
+            Path::new("<home>").join("notes.txt").display().to_string()
+        );
+        assert_eq!(registry.strip_known_prefixes(&prefixes), "<registry>");
+
+        let outside = Path::new("/tmp/build/output.log");
+        assert_eq!(
+            outside.strip_known_prefixes(&prefixes),
+            outside.display().to_string()
+        );
+        assert_eq!(
+            source.strip_known_prefixes(&[]),
+            source.display().to_string()
+        );
+    }
+
+    #[perf]
+    fn test_path_expand_tilde() {
+        assert_eq!(Path::new("~").expand_tilde(), *home_dir());
+        assert_eq!(
+            Path::new("~/a/b").expand_tilde(),
+            home_dir().join("a").join("b")
+        );
+        assert_eq!(Path::new("/a/b/~").expand_tilde(), PathBuf::from("/a/b/~"));
+        assert_eq!(
+            Path::new("~user/a").expand_tilde(),
+            PathBuf::from("~user/a")
+        );
+    }
+
+    #[perf]
+    fn test_path_normalize() {
+        assert_eq!(Path::new("a/b/../c").normalize(), PathBuf::from("a/c"));
+        assert_eq!(Path::new("./a/./b/").normalize(), PathBuf::from("a/b"));
+        assert_eq!(Path::new("a/..").normalize(), PathBuf::new());
+
+        // Leading `..` on a relative path is kept.
+        assert_eq!(Path::new("../a/b").normalize(), PathBuf::from("../a/b"));
+        assert_eq!(Path::new("a/../../b").normalize(), PathBuf::from("../b"));
+        assert_eq!(Path::new("../../a/..").normalize(), PathBuf::from("../.."));
+
+        // `..` never climbs above the root.
+        assert_eq!(Path::new("/a/../../b").normalize(), PathBuf::from("/b"));
+        assert_eq!(Path::new("/..").normalize(), PathBuf::from("/"));
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_path_normalize_windows() {
+        assert_eq!(
+            Path::new("C:\\a/b\\..\\c").normalize(),
+            PathBuf::from("C:\\a\\c")
+        );
+        assert_eq!(
+            Path::new("C:\\..\\a/./b").normalize(),
+            PathBuf::from("C:\\a\\b")
+        );
+        assert_eq!(
+            Path::new("..\\a/b/../c").normalize(),
+            PathBuf::from("..\\a\\c")
+        );
+    }
+
+    #[perf]
+    fn test_path_relativize() {
+        let base = Path::new("/home/zed/project");
+        assert_eq!(
+            Path::new("/home/zed/project/src/main.rs").relativize(base),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(
+            Path::new("/home/zed/project").relativize(base),
+            Some(PathBuf::new())
+        );
+        assert_eq!(Path::new("/home/zed/other/main.rs").relativize(base), None);
+        assert_eq!(
+            Path::new("/home/zed/project2/main.rs").relativize(base),
+            None
+        );
+    }
+
+    #[perf]
+    fn test_path_is_descendant_of() {
+        let project = Path::new("/home/zed/project");
+        assert!(Path::new("/home/zed/project/src/main.rs").is_descendant_of(project));
+        assert!(Path::new("/home/zed/project/src/").is_descendant_of(project));
+        assert!(project.is_ancestor_of(Path::new("/home/zed/project/src")));
+
+        assert!(!Path::new("/home/zed/projects").is_descendant_of(project));
+        assert!(!Path::new("a/bc").is_descendant_of(Path::new("a/b")));
+        assert!(!Path::new("a/b").is_ancestor_of(Path::new("a/bc")));
+
+        assert!(!project.is_descendant_of(project));
+        assert!(!Path::new("/home/zed/project/").is_descendant_of(project));
+        assert!(!project.is_ancestor_of(project));
+        assert!(!Path::new("/home/zed").is_descendant_of(project));
+    }
+
+    #[perf]
+    fn test_path_relative_to_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("crates/util/src")).unwrap();
+
+        let file = root.join("crates/util/src/paths.rs");
+        assert_eq!(
+            file.relative_to_marker(".git"),
+            Some((root.clone(), PathBuf::from("crates/util/src/paths.rs")))
+        );
+        assert_eq!(
+            root.relative_to_marker(".git"),
+            Some((root.clone(), PathBuf::new()))
+        );
+
+        std::fs::write(root.join("crates/util/Cargo.toml"), "").unwrap();
+        assert_eq!(
+            file.relative_to_marker("Cargo.toml"),
+            Some((root.join("crates/util"), PathBuf::from("src/paths.rs")))
+        );
+
+        assert_eq!(file.relative_to_marker("no-such-marker"), None);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_path_relativize_windows() {
+        assert_eq!(
+            Path::new("\\\\?\\C:\\Users\\zed\\project\\src\\main.rs")
+                .relativize(Path::new("C:\\Users\\zed\\project")),
+            Some(PathBuf::from("src\\main.rs"))
+        );
+        assert!(
+            Path::new("\\\\?\\C:\\Users\\zed\\project\\src")
+                .is_descendant_of(Path::new("C:\\Users\\zed\\project"))
+        );
+        assert!(
+            !Path::new("C:\\Users\\zed\\project2")
+                .is_descendant_of(Path::new("\\\\?\\C:\\Users\\zed\\project"))
+        );
+    }
+
+    #[perf]
+    fn test_common_ancestor() {
+        assert_eq!(common_ancestor([]), None);
+        assert_eq!(
+            common_ancestor([Path::new("/home/zed/project/src/main.rs")]),
+            Some(PathBuf::from("/home/zed/project/src/main.rs"))
+        );
+        assert_eq!(
+            common_ancestor([
+                Path::new("/home/zed/project/src/main.rs"),
+                Path::new("/home/zed/project/Cargo.toml"),
+                Path::new("/home/zed/project/src/lib.rs"),
+            ]),
+            Some(PathBuf::from("/home/zed/project"))
+        );
+        assert_eq!(
+            common_ancestor([
+                Path::new("/home/zed/project"),
+                Path::new("/home/zed/project/src"),
+            ]),
+            Some(PathBuf::from("/home/zed/project"))
+        );
+        assert_eq!(
+            common_ancestor([
+                Path::new("/home/zed/project2"),
+                Path::new("/home/zed/project"),
+            ]),
+            Some(PathBuf::from("/home/zed"))
+        );
+        assert_eq!(
+            common_ancestor([Path::new("/home/zed"), Path::new("/tmp")]),
+            Some(PathBuf::from("/"))
+        );
+        assert_eq!(common_ancestor([Path::new("a/b"), Path::new("c/d")]), None);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_common_ancestor_windows() {
+        assert_eq!(
+            common_ancestor([
+                Path::new("\\\\?\\C:\\Users\\zed\\project\\main.rs"),
+                Path::new("C:\\Users\\zed\\notes.txt"),
+            ]),
+            Some(PathBuf::from("C:\\Users\\zed"))
+        );
+        assert_eq!(
+            common_ancestor([
+                Path::new("C:\\Users\\zed\\main.rs"),
+                Path::new("D:\\Users\\zed\\main.rs"),
+            ]),
+            None
+        );
+    }
+
+    #[perf]
+    fn test_parse_diff_header() {
+        let both =
+            |old: &str, new: &str| Some((Some(PathBuf::from(old)), Some(PathBuf::from(new))));
+
+        assert_eq!(
+            parse_diff_header("diff --git a/src/main.rs b/src/main.rs"),
+            both("src/main.rs", "src/main.rs")
+        );
+        assert_eq!(
+            parse_diff_header("diff --git a/old name.rs b/new.rs"),
+            both("old name.rs", "new.rs")
+        );
+        assert_eq!(
+            parse_diff_header("diff --git a/my b/file.rs b/my b/file.rs"),
+            both("my b/file.rs", "my b/file.rs")
+        );
+
+        assert_eq!(
+            parse_diff_header("--- a/src/main.rs"),
+            Some((Some(PathBuf::from("src/main.rs")), None))
+        );
+        assert_eq!(
+            parse_diff_header("+++ b/src/main.rs\t2024-01-01 00:00:00"),
+            Some((None, Some(PathBuf::from("src/main.rs"))))
+        );
+
+        assert_eq!(parse_diff_header("--- /dev/null"), None);
+        assert_eq!(parse_diff_header("+++ /dev/null"), None);
+        assert_eq!(parse_diff_header("@@ -1,2 +1,2 @@"), None);
+        assert_eq!(parse_diff_header("src/main.rs"), None);
+    }
+
+    #[perf]
+    fn test_strip_diff_prefix() {
+        assert_eq!(
+            strip_diff_prefix(Path::new("a/src/main.rs")),
+            Path::new("src/main.rs")
+        );
+        assert_eq!(
+            strip_diff_prefix(Path::new("b/main.rs")),
+            Path::new("main.rs")
+        );
+        assert_eq!(
+            strip_diff_prefix(Path::new("src/main.rs")),
+            Path::new("src/main.rs")
+        );
+        assert_eq!(
+            strip_diff_prefix(Path::new("ab/main.rs")),
+            Path::new("ab/main.rs")
+        );
+        assert_eq!(strip_diff_prefix(Path::new("a")), Path::new("a"));
+        assert_eq!(
+            strip_diff_prefix(Path::new("/a/main.rs")),
+            Path::new("/a/main.rs")
+        );
+    }
+
+    #[perf]
+    fn test_path_depth() {
+        assert_eq!(path_depth(Path::new("a/b/c")), 3);
+        assert_eq!(path_depth(Path::new("/a/b/c")), 3);
+        assert_eq!(path_depth(Path::new("./a")), 1);
+        assert_eq!(path_depth(Path::new("a/./b/")), 2);
+        assert_eq!(path_depth(Path::new("../a")), 1);
+        assert_eq!(path_depth(Path::new("/")), 0);
+        assert_eq!(path_depth(Path::new(".")), 0);
+        assert_eq!(path_depth(Path::new("")), 0);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_path_depth_windows() {
+        assert_eq!(path_depth(Path::new("C:\\Users\\zed")), 2);
+        assert_eq!(path_depth(Path::new("\\\\?\\C:\\Users\\zed")), 2);
+    }
+
+    #[perf]
+    fn test_truncate_path_middle() {
+        let separator = std::path::MAIN_SEPARATOR_STR;
+        let display = |components: &[&str]| components.join(separator);
+
+        assert_eq!(
+            truncate_path_middle(Path::new("a/b/c/d/e"), 3),
+            display(&["a", "…", "e"])
+        );
+        assert_eq!(
+            truncate_path_middle(Path::new("a/b/c/d/e"), 4),
+            display(&["a", "…", "d", "e"])
+        );
+        assert_eq!(
+            truncate_path_middle(Path::new("a/b/c/d/e"), 1),
+            display(&["a", "…", "e"])
+        );
+
+        // At or under the budget the path is returned unchanged
+        assert_eq!(truncate_path_middle(Path::new("a/b/c/d/e"), 5), "a/b/c/d/e");
+        assert_eq!(truncate_path_middle(Path::new("a/b"), 3), "a/b");
+
+        // The root stays attached to the first component
+        assert_eq!(
+            truncate_path_middle(Path::new("/usr/local/share/zed/extensions"), 3),
+            format!("{separator}{}", display(&["usr", "…", "extensions"]))
+        );
+    }
+
+    #[perf]
+    fn test_extension_or_hidden_file_name() {
+        // No dots in name
+        let path = Path::new("/a/b/c/file_name.rs");
+        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+
+        // Single dot in name
+        let path = Path::new("/a/b/c/file.name.rs");
+        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+
+        // Multiple dots in name
+        let path = Path::new("/a/b/c/long.file.name.rs");
+        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+
+        // Hidden file, no extension
+        let path = Path::new("/a/b/c/.gitignore");
+        assert_eq!(path.extension_or_hidden_file_name(), Some("gitignore"));
+
+        // Hidden file, with extension
+        let path = Path::new("/a/b/c/.eslintrc.js");
+        assert_eq!(path.extension_or_hidden_file_name(), Some("eslintrc.js"));
+    }
+
+    #[perf]
+    fn test_compound_extension() {
+        let known = ["tar.gz", "gz", "d.ts"];
+
+        let path = Path::new("/a/b/c/archive.tar.gz");
+        assert_eq!(path.compound_extension(&known), Some("tar.gz"));
+        assert_eq!(path.extension_or_hidden_file_name(), Some("gz"));
+
+        let path = Path::new("/a/b/c/index.d.ts");
+        assert_eq!(path.compound_extension(&known), Some("d.ts"));
+
+        // Falls back to the single extension
+        let path = Path::new("/a/b/c/long.file.name.rs");
+        assert_eq!(path.compound_extension(&known), Some("rs"));
+        let path = Path::new("/a/b/c/file.ts");
+        assert_eq!(path.compound_extension(&known), Some("ts"));
+
+        // A known extension must follow a full `.`-separated stem
+        let path = Path::new("/a/b/c/notar.gz");
+        assert_eq!(path.compound_extension(&known), Some("gz"));
+    }
+
+    #[perf]
+    fn test_path_matcher_is_match_str() {
+        let matcher = PathMatcher::new(
+            ["**/node_modules/**", "target/", "**/*.log", "crates/util"],
+            PathStyle::Posix,
+        )
+        .unwrap();
+        for path in [
+            "",
+            "node_modules",
+            "a/node_modules",
+            "a/node_modules/b.js",
+            "target",
+            "target/debug/zed",
+            "targets/debug",
+            "a/target",
+            "debug.log",
+            "logs/debug.log.txt",
+            "crates/util",
+            "crates/util/src/paths.rs",
+            "crates/util_macros",
+            "other/crates/util",
+            "src/main.rs",
+        ] {
+            assert_eq!(
+                matcher.is_match_str(path),
+                matcher.is_match(rel_path(path)),
+                "is_match_str disagrees with is_match for {path:?}"
+            );
+        }
+
+        let matcher =
+            PathMatcher::new(["**/node_modules/**", "crates/util"], PathStyle::Windows).unwrap();
+        for (path, rel) in [
+            ("a\\node_modules", "a/node_modules"),
+            ("crates\\util\\src", "crates/util/src"),
+            ("crates\\util_macros", "crates/util_macros"),
+            ("src\\main.rs", "src/main.rs"),
+        ] {
+            assert_eq!(
+                matcher.is_match_str(path),
+                matcher.is_match(rel_path(rel)),
+                "is_match_str disagrees with is_match for {path:?}"
+            );
+        }
+    }
+
+    #[perf(important)]
+    fn test_path_matcher_is_match_str_many_paths() {
+        let matcher =
+            PathMatcher::new(["**/node_modules/**", "**/*.min.js"], PathStyle::Posix).unwrap();
+        let paths = (0..10_000)
+            .map(|ix| format!("packages/package-{ix}/src/file-{ix}.js"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths
+                .iter()
+                .filter(|path| matcher.is_match_str(path))
+                .count(),
+            0
+        );
+        assert!(matcher.is_match_str("packages/package-1/node_modules/file.js"));
+        assert!(matcher.is_match_str("packages/package-1/dist/file.min.js"));
+    }
+
+    #[perf(important)]
+    fn test_path_matcher_large_ignore_set() {
+        let globs = (0..200)
+            .map(|ix| match ix % 4 {
+                0 => format!("**/generated-{ix}/**"),
+                1 => format!("**/*.ext{ix}"),
+                2 => format!("vendor/lib-{ix}"),
+                _ => format!("**/cache-{ix}.bin"),
+            })
+            .collect::<Vec<_>>();
+        let matcher = PathMatcher::new(&globs, PathStyle::Posix).unwrap();
+        assert_eq!(matcher.len(), 200);
+
+        let paths = (0..10_000)
+            .map(|ix| {
+                let glob_ix = (ix / 4) % 200;
+                match ix % 4 {
+                    0 => (format!("vendor/lib-{glob_ix}/src/lib.rs"), glob_ix % 4 == 2),
+                    1 => (
+                        format!("packages/generated-{glob_ix}/index.js"),
+                        glob_ix % 4 == 0,
+                    ),
+                    2 => (format!("assets/file-{ix}.ext{glob_ix}"), glob_ix % 4 == 1),
+                    _ => (format!("crates/crate-{ix}/src/main.rs"), false),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (path, expected) in &paths {
+            assert_eq!(matcher.is_match(rel_path(path)), *expected, "{path}");
+            assert_eq!(matcher.is_match_str(path), *expected, "{path}");
+        }
+        assert!(paths.iter().any(|(_, expected)| *expected));
+    }
+
+    #[perf]
+    fn test_path_matcher_serde_round_trip() {
+        let matcher = PathMatcher::new(
+            ["**/*.log", "target/**", "/tmp/**", "*.{rs,toml}"],
+            PathStyle::local(),
+        )
+        .unwrap();
+        let json = serde_json::to_value(&matcher).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!(["**/*.log", "target/**", "/tmp/**", "*.{rs,toml}"])
+        );
+        assert_eq!(
+            matcher.sources().collect::<Vec<_>>(),
+            ["**/*.log", "target/**", "*.{rs,toml}"],
+            "absolute globs are serialized but aren't listed as sources"
+        );
+        let deserialized: PathMatcher = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, matcher);
+        assert_eq!(deserialized.len(), 4);
+        assert!(deserialized.is_match(rel_path("target/debug/zed")));
+
+        let empty: PathMatcher = serde_json::from_str("[]").unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[perf]
+    fn test_path_matcher_deserialize_malformed_glob() {
+        let error = serde_json::from_str::<PathMatcher>(r#"["**/*.log", "src/*.{rs"]"#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("src/*.{rs"),
+            "error should name the malformed glob: {error}"
+        );
+        assert!(serde_json::from_str::<PathMatcher>(r#""**/*.log""#).is_err());
+    }
 
+    #[perf]
+    fn test_path_matcher_builder() {
+        let mut builder = PathMatcherBuilder::new(PathStyle::Posix);
+        builder.add("**/*.log").unwrap();
+        let matcher = builder.build().unwrap();
+        assert!(matcher.is_match(rel_path("a/b/debug.log")));
+        assert!(!matcher.is_match(rel_path("target/debug/zed")));
+
+        builder.add_all(["target/**", "**/.env"]).unwrap();
+        let matcher = builder.build().unwrap();
+        assert!(matcher.is_match(rel_path("a/b/debug.log")));
+        assert!(matcher.is_match(rel_path("target/debug/zed")));
+        assert!(matcher.is_match(rel_path("crates/util/.env")));
+        assert!(!matcher.is_match(rel_path("crates/util/src/paths.rs")));
         assert_eq!(
-            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902,13):"),
-            PathWithPosition {
-                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
-                row: Some(1902),
-                column: Some(13),
-            }
+            matcher.sources().collect::<Vec<_>>(),
+            ["**/*.log", "target/**", "**/.env"]
         );
 
+        assert!(builder.add("a/{b").is_err());
+        assert_eq!(builder.build().unwrap().sources().count(), 3);
+
         assert_eq!(
-            PathWithPosition::parse_str("C:\\Users\\someone\\test_file.rs(1902):"),
-            PathWithPosition {
-                path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
-                row: Some(1902),
-                column: None,
-            }
+            PathMatcher::new(["**/*.log", "target/**", "**/.env"], PathStyle::Posix).unwrap(),
+            matcher
+        );
+    }
+
+    #[perf]
+    fn test_path_matcher_brace_alternation() {
+        let matcher = PathMatcher::new(
+            ["*.{rs,toml}", "docs/{src,{assets,theme}}/**"],
+            PathStyle::Posix,
+        )
+        .unwrap();
+        assert!(matcher.is_match(rel_path("main.rs")));
+        assert!(matcher.is_match(rel_path("Cargo.toml")));
+        assert!(!matcher.is_match(rel_path("README.md")));
+        assert!(matcher.is_match(rel_path("docs/src/index.md")));
+        assert!(matcher.is_match(rel_path("docs/theme/style.css")));
+        assert!(!matcher.is_match(rel_path("docs/book/index.html")));
+    }
+
+    #[perf]
+    fn test_path_matcher_error_names_malformed_glob() {
+        let error = PathMatcher::new(["*.rs", "src/*.{rs,toml"], PathStyle::Posix).unwrap_err();
+        assert_eq!(error.glob(), Some("src/*.{rs,toml"));
+        assert_eq!(error.kind(), &globset::ErrorKind::UnclosedAlternates);
+        assert!(
+            error.to_string().contains("src/*.{rs,toml"),
+            "error should quote the malformed glob: {error}"
         );
 
+        let mut builder = PathMatcherBuilder::new(PathStyle::Posix);
+        builder.add("*.rs").unwrap();
+        assert_eq!(builder.add("}").unwrap_err().glob(), Some("}"));
+        assert_eq!(builder.build().unwrap().len(), 1);
+    }
+
+    #[perf]
+    fn test_path_matcher_anchored() {
+        let unanchored =
+            PathMatcher::new(["target", "build/", "**/*.log"], PathStyle::Posix).unwrap();
+        let anchored = unanchored.clone().anchored(true);
+        assert_ne!(unanchored, anchored);
+
+        for (path, unanchored_match, anchored_match) in [
+            ("target", true, true),
+            ("target/debug/zed", true, true),
+            ("crates/foo/target", true, false),
+            ("targets", false, false),
+            ("build", true, true),
+            ("crates/foo/build", true, false),
+            ("debug.log", true, true),
+            ("crates/foo/debug.log", true, true),
+        ] {
+            assert_eq!(
+                unanchored.is_match(rel_path(path)),
+                unanchored_match,
+                "unanchored match for {path:?}"
+            );
+            assert_eq!(
+                anchored.is_match(rel_path(path)),
+                anchored_match,
+                "anchored match for {path:?}"
+            );
+            assert_eq!(anchored.is_match_str(path), anchored_match);
+            assert_eq!(anchored.is_match_std_path(path), anchored_match);
+        }
+    }
+
+    #[perf]
+    fn test_path_matcher_explain() {
+        let matcher = PathMatcher::new(["**/*.log", "target", "out*/"], PathStyle::Posix).unwrap();
+        let matched = |glob_index: usize, reason| MatchExplanation::Matched {
+            glob_index,
+            source: matcher.sources().nth(glob_index).unwrap().to_string(),
+            reason,
+        };
+
+        for (path, explanation) in [
+            ("logs/debug.log", matched(0, MatchReason::Glob)),
+            ("target", matched(1, MatchReason::Glob)),
+            ("target/debug", matched(1, MatchReason::LiteralPrefix)),
+            ("crates/foo/target", matched(1, MatchReason::LiteralSuffix)),
+            ("output", matched(2, MatchReason::TrailingSeparator)),
+            ("src/main.rs", MatchExplanation::NoMatch),
+        ] {
+            assert_eq!(matcher.explain(rel_path(path)), explanation, "{path:?}");
+            assert_eq!(
+                explanation.is_match(),
+                matcher.is_match(rel_path(path)),
+                "{path:?}"
+            );
+        }
+
+        let anchored = matcher.anchored(true);
         assert_eq!(
-            PathWithPosition::parse_str("crates/utils/paths.rs:101"),
-            PathWithPosition {
-                path: PathBuf::from("crates\\utils\\paths.rs"),
-                row: Some(101),
-                column: None,
-            }
+            anchored.explain(rel_path("crates/foo/target")),
+            MatchExplanation::NoMatch
+        );
+        assert_eq!(
+            anchored.explain(rel_path("output")),
+            MatchExplanation::NoMatch
         );
     }
 
     #[perf]
-    fn test_path_compact() {
-        let path: PathBuf = [
-            home_dir().to_string_lossy().into_owned(),
-            "some_file.txt".to_string(),
-        ]
-        .iter()
-        .collect();
-        if cfg!(any(target_os = "linux", target_os = "freebsd")) || cfg!(target_os = "macos") {
-            assert_eq!(path.compact().to_str(), Some("~/some_file.txt"));
-        } else {
-            assert_eq!(path.compact().to_str(), path.to_str());
+    fn test_path_matcher_equality_ignores_glob_order() {
+        let matcher = PathMatcher::new(["*.rs", "target", "build/"], PathStyle::Posix).unwrap();
+        let reordered =
+            PathMatcher::new(["build/", "*.rs", "target", "*.rs"], PathStyle::Posix).unwrap();
+        assert_eq!(matcher, reordered);
+        assert_eq!(
+            reordered.sources().collect::<Vec<_>>(),
+            ["build/", "*.rs", "target", "*.rs"]
+        );
+        for path in ["src/main.rs", "target/debug", "build", "README.md"] {
+            assert_eq!(
+                matcher.is_match(rel_path(path)),
+                reordered.is_match(rel_path(path)),
+                "match for {path:?}"
+            );
         }
+
+        let narrower = PathMatcher::new(["*.rs", "target"], PathStyle::Posix).unwrap();
+        assert_ne!(matcher, narrower);
+        assert_ne!(matcher, reordered.anchored(true));
     }
 
     #[perf]
-    fn test_extension_or_hidden_file_name() {
-        // No dots in name
-        let path = Path::new("/a/b/c/file_name.rs");
-        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+    fn test_path_matcher_match_any_component() {
+        let matcher = PathMatcher::new(
+            ["node_modules", ".git", "*.log", "crates/util"],
+            PathStyle::Posix,
+        )
+        .unwrap();
+        assert!(matcher.match_any_component(rel_path("web/node_modules/react/index.js")));
+        assert!(matcher.match_any_component(rel_path("web/node_modules")));
+        assert!(matcher.match_any_component(rel_path(".git")));
+        assert!(!matcher.is_match(rel_path("web/node_modules/react/index.js")));
 
-        // Single dot in name
-        let path = Path::new("/a/b/c/file.name.rs");
-        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+        assert!(!matcher.match_any_component(rel_path("web/node_modules_cache/index.js")));
+        assert!(!matcher.match_any_component(rel_path("logs/debug.log")));
+        assert!(!matcher.match_any_component(rel_path("other/crates/util/src")));
+        assert!(!matcher.match_any_component(rel_path("")));
+    }
 
-        // Multiple dots in name
-        let path = Path::new("/a/b/c/long.file.name.rs");
-        assert_eq!(path.extension_or_hidden_file_name(), Some("rs"));
+    #[perf]
+    fn test_path_matcher_is_empty() {
+        let empty = PathMatcher::new(&[] as &[&str], PathStyle::Posix).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+        for path in ["", "a", "a/b.rs", "node_modules/x/y.js"] {
+            assert!(!empty.is_match(rel_path(path)));
+            assert!(!empty.is_match_str(path));
+        }
 
-        // Hidden file, no extension
-        let path = Path::new("/a/b/c/.gitignore");
-        assert_eq!(path.extension_or_hidden_file_name(), Some("gitignore"));
+        let matcher = PathMatcher::new(["**/node_modules/**", "*.rs"], PathStyle::Posix).unwrap();
+        assert!(!matcher.is_empty());
+        assert_eq!(matcher.len(), 2);
+        assert!(matcher.is_match(rel_path("a/node_modules/x/y.js")));
+        assert!(matcher.is_match(rel_path("main.rs")));
+        assert!(!matcher.is_match(rel_path("main.js")));
+        assert!(matcher.is_match_str("main.rs"));
+        assert!(!matcher.is_match_str("main.js"));
+    }
 
-        // Hidden file, with extension
-        let path = Path::new("/a/b/c/.eslintrc.js");
-        assert_eq!(path.extension_or_hidden_file_name(), Some("eslintrc.js"));
+    #[perf]
+    fn test_path_matcher_from_borrowed_and_lazy_globs() {
+        let globs: &[&str] = &["**/*.log", "target/**"];
+        let from_slice = PathMatcher::new(globs, PathStyle::Posix).unwrap();
+        assert!(from_slice.is_match(rel_path("a/debug.log")));
+        assert!(from_slice.is_match(rel_path("target/debug/zed")));
+
+        let settings = "**/*.log, target/**";
+        let from_iterator =
+            PathMatcher::new(settings.split(',').map(str::trim), PathStyle::Posix).unwrap();
+        assert_eq!(from_iterator, from_slice);
+
+        let owned = vec!["**/*.log".to_string(), "target/**".to_string()];
+        assert_eq!(
+            PathMatcher::new(&owned, PathStyle::Posix).unwrap(),
+            from_slice
+        );
     }
 
     #[perf]
@@ -2853,6 +5805,206 @@ mod tests {
         );
     }
 
+    #[perf]
+    fn test_sanitized_path_from_checked() {
+        let path = Path::new("/home/someone/test_file.rs");
+        let (sanitized_path, changed) = SanitizedPath::from_checked(path);
+        assert_eq!(sanitized_path.as_path(), path);
+        assert!(!changed);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_sanitized_path_from_checked_windows() {
+        let (sanitized_path, changed) =
+            SanitizedPath::from_checked(Path::new("\\\\?\\C:\\Users\\someone\\test_file.rs"));
+        assert_eq!(
+            sanitized_path.to_string(),
+            "C:\\Users\\someone\\test_file.rs"
+        );
+        assert!(changed);
+
+        let (sanitized_path, changed) =
+            SanitizedPath::from_checked(Path::new("C:\\Users\\someone\\test_file.rs"));
+        assert_eq!(
+            sanitized_path.to_string(),
+            "C:\\Users\\someone\\test_file.rs"
+        );
+        assert!(!changed);
+    }
+
+    #[perf]
+    fn test_sanitized_path_join_raw() {
+        let base = SanitizedPath::new(Path::new("/home/someone"));
+        assert_eq!(
+            base.join_raw("foo/bar").as_path(),
+            Path::new("/home/someone/foo/bar")
+        );
+        assert_eq!(
+            base.join_raw("/etc/hosts").as_path(),
+            Path::new("/etc/hosts")
+        );
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_sanitized_path_join_raw_windows() {
+        let base = SanitizedPath::new(Path::new("\\\\?\\C:\\Users\\someone"));
+        assert_eq!(
+            base.join_raw("foo\\bar").to_string(),
+            "C:\\Users\\someone\\foo\\bar"
+        );
+        assert_eq!(
+            base.join_raw("\\\\?\\D:\\project").to_string(),
+            "D:\\project"
+        );
+    }
+
+    #[perf]
+    fn test_sanitized_path_serde_round_trip() {
+        let path = SanitizedPath::new(Path::new("/home/someone/test_file.rs"));
+        let json = serde_json::to_string(path).unwrap();
+        assert_eq!(json, "\"/home/someone/test_file.rs\"");
+
+        let deserialized: Arc<SanitizedPath> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.as_ref(), path);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_sanitized_path_deserialize_strips_unc() {
+        let deserialized: Box<SanitizedPath> =
+            serde_json::from_str(r#""\\\\?\\C:\\Users\\someone\\test_file.rs""#).unwrap();
+        assert_eq!(deserialized.to_string(), "C:\\Users\\someone\\test_file.rs");
+
+        let json = serde_json::to_string(deserialized.as_ref()).unwrap();
+        assert_eq!(json, r#""C:\\Users\\someone\\test_file.rs""#);
+    }
+
+    #[perf]
+    fn test_paths_equal() {
+        for (a, b) in [
+            ("a/b/", "a/b"),
+            ("a/./b", "a/b"),
+            ("./a/b", "a/b"),
+            ("a/b/../c", "a/c"),
+            ("/root/a/.", "/root/a"),
+            ("../a/./b", "../a/b"),
+            ("/a/../../b", "/b"),
+            (".", ""),
+        ] {
+            assert!(paths_equal(Path::new(a), Path::new(b)), "{a:?} == {b:?}");
+            assert!(paths_equal(Path::new(b), Path::new(a)), "{b:?} == {a:?}");
+        }
+        for (a, b) in [
+            ("a/b", "a/c"),
+            ("a/b", "/a/b"),
+            ("a/b/..", "a/b"),
+            ("../a", "a"),
+        ] {
+            assert!(!paths_equal(Path::new(a), Path::new(b)), "{a:?} != {b:?}");
+        }
+    }
+
+    #[perf]
+    fn test_canonicalize_best_effort() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize_best_effort(dir.path());
+        assert!(root.is_absolute());
+        assert!(!root.to_string_lossy().starts_with("\\\\?\\"));
+
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        assert_eq!(
+            canonicalize_best_effort(&root.join("src/../src/./main.rs")),
+            root.join("src").join("main.rs")
+        );
+
+        assert_eq!(
+            canonicalize_best_effort(&root.join("missing/./a/../b.rs")),
+            root.join("missing").join("b.rs")
+        );
+
+        assert_eq!(
+            canonicalize_best_effort(Path::new("missing-dir/./nested/../file.rs")),
+            std::env::current_dir()
+                .unwrap()
+                .join("missing-dir")
+                .join("file.rs")
+        );
+    }
+
+    #[perf]
+    fn test_is_absolute_sanitized() {
+        assert!(is_absolute_sanitized(&std::env::current_dir().unwrap()));
+        assert!(!is_absolute_sanitized(Path::new("a/b")));
+        assert!(!is_absolute_sanitized(Path::new("./a")));
+        assert!(!is_absolute_sanitized(Path::new("")));
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_is_absolute_sanitized_windows() {
+        for path in [
+            "\\\\?\\C:\\x",
+            "C:\\x",
+            "C:/x",
+            "\\\\server\\share",
+            "\\\\server\\share\\x",
+            "\\\\?\\UNC\\server\\share\\x",
+        ] {
+            assert!(is_absolute_sanitized(Path::new(path)), "{path:?}");
+        }
+        for path in ["a\\b", "C:x", "\\x"] {
+            assert!(!is_absolute_sanitized(Path::new(path)), "{path:?}");
+        }
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_paths_equal_windows() {
+        for (a, b) in [
+            ("C:\\Users\\someone\\", "C:\\Users\\someone"),
+            ("C:/Users/someone/file.rs", "C:\\Users\\someone\\file.rs"),
+            ("C:\\Users\\.\\someone", "C:/Users/someone"),
+            ("\\\\?\\C:\\Users\\someone", "C:\\Users\\someone"),
+        ] {
+            assert!(paths_equal(Path::new(a), Path::new(b)), "{a:?} == {b:?}");
+        }
+        assert!(!paths_equal(
+            Path::new("C:\\Users\\someone"),
+            Path::new("D:\\Users\\someone")
+        ));
+    }
+
+    #[perf]
+    fn test_sanitized_path_navigation() {
+        let path = SanitizedPath::new(Path::new("/home/someone/test_file.tar.gz"));
+        assert_eq!(path.file_name(), Some(OsStr::new("test_file.tar.gz")));
+        assert_eq!(path.extension(), Some(OsStr::new("gz")));
+
+        let parent = path.parent().unwrap();
+        assert_eq!(parent.as_path(), Path::new("/home/someone"));
+        assert_eq!(parent.file_name(), Some(OsStr::new("someone")));
+        assert_eq!(parent.extension(), None);
+        assert!(path.starts_with(parent));
+
+        let root = SanitizedPath::new(Path::new("/"));
+        assert_eq!(root.parent(), None);
+        assert_eq!(root.file_name(), None);
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_sanitized_path_parent_of_unc_path() {
+        let path = SanitizedPath::new(Path::new("\\\\?\\C:\\Users\\someone\\test_file.rs"));
+        let parent = path.parent().unwrap();
+        assert_eq!(parent.to_string(), "C:\\Users\\someone");
+        assert_eq!(parent.parent().unwrap().to_string(), "C:\\Users");
+        assert_eq!(path.file_name(), Some(OsStr::new("test_file.rs")));
+        assert_eq!(path.extension(), Some(OsStr::new("rs")));
+    }
+
     #[perf]
     fn test_compare_numeric_segments() {
         // Helper function to create peekable iterators and test
@@ -2911,6 +6063,19 @@ mod tests {
             Ordering::Greater
         );
 
+        // Numbers too long for any integer type
+        let forty_nines = "9".repeat(40);
+        let one_and_forty_zeros = format!("1{}", "0".repeat(40));
+        assert_eq!(compare(&forty_nines, &one_and_forty_zeros), Ordering::Less);
+        assert_eq!(
+            compare(&one_and_forty_zeros, &forty_nines),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare(&format!("0{forty_nines}"), &forty_nines),
+            Ordering::Greater
+        );
+
         // Iterator advancement verification
         let mut a_iter = "123abc".chars().peekable();
         let mut b_iter = "456def".chars().peekable();
@@ -3200,6 +6365,233 @@ mod tests {
         assert_eq!(natural_sort("file-1.10", "file-1.2"), Ordering::Greater);
     }
 
+    #[perf]
+    fn test_natural_sort_paths() {
+        assert_eq!(natural_sort("a/b.txt", "a-b.txt"), Ordering::Greater);
+        assert_eq!(natural_sort_paths("a/b.txt", "a-b.txt"), Ordering::Less);
+        assert_eq!(natural_sort_paths("a/z.txt", "a.txt"), Ordering::Less);
+        assert_eq!(natural_sort_paths("a/z.txt", "a_b.txt"), Ordering::Less);
+
+        let mut paths = vec![
+            "a-b.txt",
+            "a/file10.rs",
+            "a.txt",
+            "a/file2.rs",
+            "a/B/c.rs",
+            "b.txt",
+        ];
+        paths.sort_by(|a, b| natural_sort_paths(a, b));
+        assert_eq!(
+            paths,
+            [
+                "a/B/c.rs",
+                "a/file2.rs",
+                "a/file10.rs",
+                "a-b.txt",
+                "a.txt",
+                "b.txt"
+            ]
+        );
+
+        // Everything else still compares like `natural_sort`.
+        assert_eq!(natural_sort_paths("File2", "file10"), Ordering::Less);
+        assert_eq!(natural_sort_paths("apple", "Apple"), Ordering::Less);
+        assert_eq!(natural_sort_paths("a/b", "a/b"), Ordering::Equal);
+    }
+
+    #[perf]
+    fn test_natural_sort_case_folded() {
+        // ASCII-only folding orders `Ä` before `ä` regardless of the rest of the name.
+        assert_eq!(natural_sort("Äz", "äa"), Ordering::Less);
+        assert_eq!(natural_sort_case_folded("Äz", "äa"), Ordering::Greater);
+        assert_eq!(natural_sort_case_folded("Ärger", "ärmel"), Ordering::Less);
+        assert_eq!(natural_sort_case_folded("Σίγμα", "σίγμα2"), Ordering::Less);
+
+        // Original case is the final tie-breaker, lowercase first.
+        assert_eq!(natural_sort_case_folded("école", "École"), Ordering::Less);
+        assert_eq!(
+            natural_sort_case_folded("École", "école"),
+            Ordering::Greater
+        );
+        assert_eq!(natural_sort_case_folded("École", "École"), Ordering::Equal);
+
+        // Accents are not folded away.
+        assert_eq!(
+            natural_sort_case_folded("ärmel", "zebra"),
+            Ordering::Greater
+        );
+
+        // ASCII names and numbers behave like `natural_sort`.
+        assert_eq!(natural_sort_case_folded("File2", "file10"), Ordering::Less);
+        assert_eq!(
+            natural_sort_case_folded("Apple", "apple"),
+            Ordering::Greater
+        );
+    }
+
+    #[perf]
+    fn compare_rel_paths_is_stable_for_names_equal_under_order() {
+        let orders = [
+            SortOrder::Default,
+            SortOrder::Upper,
+            SortOrder::Lower,
+            SortOrder::Unicode,
+            SortOrder::CaseFolded,
+            SortOrder::Version,
+            SortOrder::Decimal,
+        ];
+        let modes = [
+            SortMode::DirectoriesFirst,
+            SortMode::Mixed,
+            SortMode::FilesFirst,
+        ];
+        for order in orders {
+            for mode in modes {
+                let forward = sorted_rel_paths(
+                    vec![
+                        rel_path_entry("File-1.RS", true),
+                        rel_path_entry("File-1.rs", true),
+                    ],
+                    mode,
+                    order,
+                );
+                let backward = sorted_rel_paths(
+                    vec![
+                        rel_path_entry("File-1.rs", true),
+                        rel_path_entry("File-1.RS", true),
+                    ],
+                    mode,
+                    order,
+                );
+                assert_eq!(forward, backward, "{mode:?} {order:?}");
+                assert_ne!(
+                    compare_rel_paths_by(
+                        forward[0],
+                        forward[1],
+                        mode,
+                        order,
+                        SortDirection::Ascending
+                    ),
+                    Ordering::Equal,
+                    "{mode:?} {order:?}"
+                );
+            }
+        }
+
+        assert_eq!(
+            compare_rel_paths(
+                rel_path_entry("dir/a.RS", true),
+                rel_path_entry("dir/a.rs", true)
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_rel_paths(
+                rel_path_entry("dir/a.rs", true),
+                rel_path_entry("dir/a.rs", true)
+            ),
+            Ordering::Equal
+        );
+    }
+
+    #[perf]
+    fn compare_rel_paths_case_folded() {
+        let paths = vec![
+            rel_path_entry("Österreich", false),
+            rel_path_entry("zebra", false),
+            rel_path_entry("öffnen", false),
+            rel_path_entry("Öl.txt", true),
+            rel_path_entry("ödland.txt", true),
+            rel_path_entry("apple.txt", true),
+        ];
+        assert_eq!(
+            sorted_rel_paths(paths, SortMode::DirectoriesFirst, SortOrder::CaseFolded),
+            vec![
+                rel_path_entry("zebra", false),
+                rel_path_entry("öffnen", false),
+                rel_path_entry("Österreich", false),
+                rel_path_entry("apple.txt", true),
+                rel_path_entry("ödland.txt", true),
+                rel_path_entry("Öl.txt", true),
+            ]
+        );
+    }
+
+    #[perf]
+    fn test_natural_sort_long_digit_runs() {
+        let hash_a = format!("{}.bin", "1".repeat(30));
+        let hash_b = format!("{}.bin", "2".repeat(29));
+        let hash_c = format!("{}.bin", "9".repeat(45));
+        let hash_d = format!("1{}.bin", "0".repeat(45));
+
+        let mut names = vec![&hash_d, &hash_a, &hash_c, &hash_b];
+        names.sort_by(|a, b| natural_sort(a, b));
+        assert_eq!(names, vec![&hash_b, &hash_a, &hash_c, &hash_d]);
+
+        names.reverse();
+        names.sort_by(|a, b| natural_sort(a, b));
+        assert_eq!(names, vec![&hash_b, &hash_a, &hash_c, &hash_d]);
+    }
+
+    #[perf]
+    fn test_natural_sort_with_decimals() {
+        // Decimals compare by value rather than digit run by digit run.
+        assert_eq!(
+            natural_sort_with_decimals("scale-0.25.png", "scale-0.5.png"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_sort_with_decimals("file-1.5.txt", "file-1.10.txt"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            natural_sort_with_decimals("file-1.5.txt", "file-1.50.txt"),
+            Ordering::Less
+        );
+        assert_eq!(natural_sort_with_decimals("1.5", "1.5"), Ordering::Equal);
+
+        // A leading `-` before digits is a minus sign.
+        assert_eq!(
+            natural_sort_with_decimals("-3.txt", "-1.txt"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_sort_with_decimals("-1.txt", "2.txt"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_sort_with_decimals("offset_-10", "offset_-2"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_sort_with_decimals("offset_-2", "offset_3"),
+            Ordering::Less
+        );
+        assert_eq!(natural_sort_with_decimals("-0.5", "-0.25"), Ordering::Less);
+
+        // After a letter or digit, `-` remains a separator.
+        assert_eq!(
+            natural_sort_with_decimals("file-3", "file-10"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural_sort_with_decimals("2024-05", "2024-11"),
+            Ordering::Less
+        );
+
+        // Plain filenames behave like `natural_sort`.
+        for (a, b) in [
+            ("file2", "file10"),
+            ("file002", "file2"),
+            ("File", "file"),
+            ("a.txt", "b.txt"),
+            ("", "a"),
+        ] {
+            assert_eq!(natural_sort_with_decimals(a, b), natural_sort(a, b));
+            assert_eq!(natural_sort_with_decimals(b, a), natural_sort(b, a));
+        }
+    }
+
     #[test]
     fn test_multiple_extensions() {
         // No extensions
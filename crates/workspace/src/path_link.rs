@@ -175,6 +175,8 @@ fn possible_open_target_internal(
                 path: stripped.to_owned(),
                 row: original_path.row,
                 column: original_path.column,
+                offset: original_path.offset,
+                context: original_path.context.clone(),
             });
         }
         if let Some(stripped) = path_with_position.path.strip_prefix(prefix_str).ok() {
@@ -182,6 +184,8 @@ fn possible_open_target_internal(
                 path: stripped.to_owned(),
                 row: path_with_position.row,
                 column: path_with_position.column,
+                offset: path_with_position.offset,
+                context: path_with_position.context.clone(),
             });
         }
     }
@@ -214,6 +218,8 @@ fn possible_open_target_internal(
                     path: worktree_root.to_path_buf(),
                     row: path_with_position.row,
                     column: path_with_position.column,
+                    offset: path_with_position.offset,
+                    context: path_with_position.context.clone(),
                 };
                 match worktree.read(cx).root_entry() {
                     Some(root_entry) => {
@@ -236,6 +242,8 @@ fn possible_open_target_internal(
                         .to_owned(),
                     row: path_with_position.row,
                     column: path_with_position.column,
+                    offset: path_with_position.offset,
+                    context: path_with_position.context.clone(),
                 }
             };
 
@@ -271,6 +279,8 @@ fn possible_open_target_internal(
                         path: worktree.read(cx).absolutize(&entry.path),
                         row: path_to_check.row,
                         column: path_to_check.column,
+                        offset: path_to_check.offset,
+                        context: path_to_check.context.clone(),
                     },
                     entry.clone(),
                     #[cfg(any(test, feature = "test-support"))]
@@ -309,6 +319,8 @@ fn possible_open_target_internal(
                             path: cwd.join(maybe_path),
                             row: path_to_check.row,
                             column: path_to_check.column,
+                            offset: path_to_check.offset,
+                            context: path_to_check.context.clone(),
                         });
                     }
                 }
@@ -333,6 +345,8 @@ fn possible_open_target_internal(
                                     path: home_path,
                                     row: path_to_check.row,
                                     column: path_to_check.column,
+                                    offset: path_to_check.offset,
+                                    context: path_to_check.context.clone(),
                                 });
                             }
                         } else {
@@ -340,6 +354,8 @@ fn possible_open_target_internal(
                                 path: maybe_path.clone(),
                                 row: path_to_check.row,
                                 column: path_to_check.column,
+                                offset: path_to_check.offset,
+                                context: path_to_check.context.clone(),
                             });
                             if maybe_path.is_relative() {
                                 for worktree in &worktree_candidates {
@@ -348,6 +364,8 @@ fn possible_open_target_internal(
                                             path: worktree.read(cx).abs_path().join(maybe_path),
                                             row: path_to_check.row,
                                             column: path_to_check.column,
+                                            offset: path_to_check.offset,
+                                            context: path_to_check.context.clone(),
                                         });
                                     }
                                 }
@@ -403,6 +421,8 @@ fn possible_open_target_internal(
                                         path: worktree.absolutize(&entry.path),
                                         row: path_in_worktree.row,
                                         column: path_in_worktree.column,
+                                        offset: path_in_worktree.offset,
+                                        context: path_in_worktree.context.clone(),
                                     },
                                     entry.clone(),
                                     #[cfg(any(test, feature = "test-support"))]